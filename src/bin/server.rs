@@ -3,6 +3,7 @@
 use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
 use std::thread;
+use std::time::Duration;
 
 // The server uses the same core logic from the library crate.
 use rust_dbms::{
@@ -11,6 +12,10 @@ use rust_dbms::{
     execute_line,
 };
 
+/// How long a client may take to send its request, so a client that never shuts down its
+/// write half cannot hold a connection and its thread open indefinitely.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// Handles a single client connection.
 ///
 /// This function is spawned in a new thread for each incoming connection.
@@ -26,12 +31,19 @@ fn handle_client(mut stream: TcpStream) {
     let mut query_engine = QueryEngine::new();
     let parser = Parser::new();
 
-    let mut buffer = [0; 4096]; // Increased buffer size for potentially larger queries
+    let mut buffer = Vec::new();
 
-    // A connection now handles a single query and then closes.
+    // A connection now handles a single request and then closes.
     // This is a simpler request/response model that prevents the client from hanging
-    // while waiting for a stream to end.
-    match stream.read(&mut buffer) {
+    // while waiting for a stream to end. The request is framed by EOF: the client sends
+    // the statements and then shuts down its write half (e.g. `shutdown(Shutdown::Write)`),
+    // so a script of any length is read in full. A client that does not do so within
+    // `READ_TIMEOUT` is disconnected.
+    if let Err(e) = stream.set_read_timeout(Some(READ_TIMEOUT)) {
+        eprintln!("Failed to set read timeout: {}", e);
+        return;
+    }
+    match stream.read_to_end(&mut buffer) {
         Ok(size) => {
             // If size is 0, the client connected and disconnected without sending data.
             if size == 0 {
@@ -40,9 +52,10 @@ fn handle_client(mut stream: TcpStream) {
             }
 
             // Convert the received bytes to a string, trimming whitespace.
-            let input = String::from_utf8_lossy(&buffer[..size]).trim().to_string();
+            let input = String::from_utf8_lossy(&buffer).trim().to_string();
 
-            // Process the command using the shared `execute_line` function.
+            // Process the request using the shared `execute_line` function. The input may be a
+            // script of several statements separated by semicolons.
             if !input.is_empty() {
                 let result = execute_line(&input, &mut query_engine, &parser);
                 
@@ -52,6 +65,9 @@ fn handle_client(mut stream: TcpStream) {
                 }
             }
         }
+        Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {
+            eprintln!("Client did not finish sending its request within {:?}", READ_TIMEOUT);
+        }
         Err(e) => {
             eprintln!("An error occurred reading from connection: {}", e);
         }
@@ -1,24 +1,66 @@
-use crate::engine::{Catalog, Row, Table};
-use crate::parser::Column;
-use crate::storage::{Page, StorageEngine};
+use crate::engine::{Catalog, Row, Sequence, Table};
+use crate::parser::{parse_data_type, unquote, Column, Expr, ForeignKey, Literal, Parser, ReferentialAction, TableConstraint};
+use crate::storage::{Page, StorageEngine, PAGE_SIZE};
+use uuid::Uuid;
 
 // Database file format constants
 const MAGIC_NUMBER: u64 = 0x4953454E54414442; // "ISENTADB" in hex
 const DB_VERSION: u32 = 1;
 const HEADER_PAGE_ID: u64 = 0;
+const FREE_LIST_OFFSET: usize = PAGE_SIZE - 8;
 
 // Value type tags for binary encoding
 const TYPE_NULL: u8 = 0;
 const TYPE_INT: u8 = 1;
 const TYPE_TEXT: u8 = 2;
+const TYPE_UUID: u8 = 3;
+
+// Column flag bits stored in the schema page
+const COLUMN_PRIMARY_KEY: u8 = 0x01;
+const COLUMN_NOT_NULL: u8 = 0x02;
+const COLUMN_UNIQUE: u8 = 0x04;
+const COLUMN_AUTO_INCREMENT: u8 = 0x08;
+
+// Table constraint kinds stored in the schema page
+const CONSTRAINT_CHECK: u8 = 1;
+const CONSTRAINT_FOREIGN_KEY: u8 = 2;
+
+// ON DELETE actions of foreign keys
+const ACTION_NO_ACTION: u8 = 0;
+const ACTION_RESTRICT: u8 = 1;
+const ACTION_CASCADE: u8 = 2;
+const ACTION_SET_NULL: u8 = 3;
 
 // Header page layout (Page 0):
 // Offset 0-7:   Magic number (u64)
 // Offset 8-11:  Version (u32)
 // Offset 12-19: Schema root page ID (u64)
 // Offset 20-23: Number of tables (u32)
+// Offset 24-31: First sequence page ID (u64, 0 = no sequences)
+// Offset 4088-4095: First page of the free list (u64, 0 = none)
 // Rest: Reserved
 
+// Free pages form a chain: the first 8 bytes of a free page hold the ID of the next one.
+
+// Sequence pages form a chain: next sequence page ID (u64, 0 = last), number of sequences on
+// the page (u32), then per sequence: name length (u32), name, increment (i64), last value
+// handed out (i64).
+const SEQUENCE_PAGE_HEADER_SIZE: usize = 12;
+
+// Schema page layout (one page per table):
+// Table name length (u32) and name
+// Number of columns (u32), then per column: name length (u32), name, type length (u32), type
+// Data page ID (u64)
+// Next schema page ID (u64)
+// Column flags (u8 per column)
+// Column defaults: per column, expression length (u32, 0 = no default) and expression
+// Table constraints: count (u32), then per constraint a kind (u8) and its payload:
+//   CHECK: expression length (u32) and expression
+//   FOREIGN KEY: column count (u32) and column names, parent table name, parent column
+//     count (u32) and column names, ON DELETE action (u8); names are length-prefixed (u32)
+// The sections after the next page ID were added later; pages written before they
+// existed are zero-filled there, which reads back as "no flags".
+
 pub struct Database {
     storage: StorageEngine,
 }
@@ -150,7 +192,7 @@ impl Database {
         // If we loaded fewer tables than expected, update the count
         if tables_loaded != num_tables {
             eprintln!("Warning: Expected {} tables but only loaded {}. Repairing database...", num_tables, tables_loaded);
-            header.data[20..24].copy_from_slice(&(tables_loaded as u32).to_le_bytes());
+            header.data[20..24].copy_from_slice(&tables_loaded.to_le_bytes());
             self.storage.write_page(&header);
         }
 
@@ -162,6 +204,226 @@ impl Database {
         Ok(catalog)
     }
 
+    /// Locks the database file, waiting until no other connection to it holds the lock.
+    pub fn lock(&mut self) -> Result<(), String> {
+        self.storage.file().lock().map_err(|e| format!("Failed to lock database file: {}", e))
+    }
+
+    pub fn unlock(&mut self) -> Result<(), String> {
+        self.storage.file().unlock().map_err(|e| format!("Failed to unlock database file: {}", e))
+    }
+
+    /// Loads the sequences stored on the sequence pages.
+    pub fn load_sequences(&mut self) -> Result<Vec<Sequence>, String> {
+        let header = self.storage.read_page(HEADER_PAGE_ID);
+        let mut sequences = Vec::new();
+        for page_id in self.sequence_page_ids(&header) {
+            let page = self.storage.read_page(page_id);
+            let count = u32::from_le_bytes(page.data[8..12].try_into().unwrap());
+            let mut offset = SEQUENCE_PAGE_HEADER_SIZE;
+            for _ in 0..count {
+                let name = Self::read_string(&page, &mut offset)
+                    .ok_or("Failed to read sequence name")?;
+                if offset + 16 > page.data.len() {
+                    return Err("Failed to read sequence values".to_string());
+                }
+                let increment = i64::from_le_bytes(page.data[offset..offset + 8].try_into().unwrap());
+                let last_value = i64::from_le_bytes(page.data[offset + 8..offset + 16].try_into().unwrap());
+                offset += 16;
+                sequences.push(Sequence { name, increment, last_value });
+            }
+        }
+        Ok(sequences)
+    }
+
+    /// Writes all sequences to the sequence pages, replacing the ones stored before. The pages
+    /// already in use are written over, so saving a sequence's new value allocates no pages.
+    pub fn save_sequences(&mut self, sequences: &[Sequence]) -> Result<(), String> {
+        // Lay the sequences out on as many pages as they need
+        let mut layout: Vec<&[Sequence]> = Vec::new();
+        let (mut start, mut used) = (0, SEQUENCE_PAGE_HEADER_SIZE);
+        for (index, sequence) in sequences.iter().enumerate() {
+            let size = 4 + sequence.name.len() + 16;
+            if SEQUENCE_PAGE_HEADER_SIZE + size > PAGE_SIZE {
+                return Err(format!("Sequence name '{}' is too long", sequence.name));
+            }
+            if used + size > PAGE_SIZE {
+                layout.push(&sequences[start..index]);
+                (start, used) = (index, SEQUENCE_PAGE_HEADER_SIZE);
+            }
+            used += size;
+        }
+        if start < sequences.len() {
+            layout.push(&sequences[start..]);
+        }
+
+        let header = self.storage.read_page(HEADER_PAGE_ID);
+        let mut page_ids = self.sequence_page_ids(&header);
+        while page_ids.len() < layout.len() {
+            page_ids.push(self.allocate_page().id);
+        }
+        for page_id in page_ids.split_off(layout.len()) {
+            self.free_page(page_id);
+        }
+
+        for (position, entries) in layout.iter().enumerate() {
+            let mut page = Page::new(page_ids[position]);
+            let next_id = page_ids.get(position + 1).copied().unwrap_or(0);
+            page.data[0..8].copy_from_slice(&next_id.to_le_bytes());
+            page.data[8..12].copy_from_slice(&(entries.len() as u32).to_le_bytes());
+            let mut offset = SEQUENCE_PAGE_HEADER_SIZE;
+            for sequence in entries.iter() {
+                Self::write_string(&mut page, &mut offset, &sequence.name)?;
+                page.data[offset..offset + 8].copy_from_slice(&sequence.increment.to_le_bytes());
+                page.data[offset + 8..offset + 16].copy_from_slice(&sequence.last_value.to_le_bytes());
+                offset += 16;
+            }
+            self.storage.write_page(&page);
+        }
+
+        // Allocating and freeing pages changed the header, so it is read again
+        let mut header = self.storage.read_page(HEADER_PAGE_ID);
+        header.data[24..32].copy_from_slice(&page_ids.first().copied().unwrap_or(0).to_le_bytes());
+        self.storage.write_page(&header);
+        Ok(())
+    }
+
+    /// Returns the IDs of the sequence pages, in order.
+    fn sequence_page_ids(&mut self, header: &Page) -> Vec<u64> {
+        let mut page_ids = Vec::new();
+        let mut page_id = u64::from_le_bytes(header.data[24..32].try_into().unwrap());
+        while page_id != 0 && !page_ids.contains(&page_id) {
+            page_ids.push(page_id);
+            page_id = u64::from_le_bytes(self.storage.read_page(page_id).data[0..8].try_into().unwrap());
+        }
+        page_ids
+    }
+
+    /// Returns a zero-filled page, reusing a page from the free list when there is one.
+    fn allocate_page(&mut self) -> Page {
+        let mut header = self.storage.read_page(HEADER_PAGE_ID);
+        let free_head = u64::from_le_bytes(header.data[FREE_LIST_OFFSET..].try_into().unwrap());
+        if free_head == 0 {
+            return self.storage.allocate_page();
+        }
+
+        let free_page = self.storage.read_page(free_head);
+        header.data[FREE_LIST_OFFSET..].copy_from_slice(&free_page.data[0..8]);
+        self.storage.write_page(&header);
+
+        let page = Page::new(free_head);
+        self.storage.write_page(&page);
+        page
+    }
+
+    /// Puts a page on the free list so a later allocation can reuse it.
+    fn free_page(&mut self, page_id: u64) {
+        if page_id == HEADER_PAGE_ID {
+            return;
+        }
+        let mut header = self.storage.read_page(HEADER_PAGE_ID);
+
+        let mut page = Page::new(page_id);
+        page.data[0..8].copy_from_slice(&header.data[FREE_LIST_OFFSET..]);
+        self.storage.write_page(&page);
+
+        header.data[FREE_LIST_OFFSET..].copy_from_slice(&page_id.to_le_bytes());
+        self.storage.write_page(&header);
+    }
+
+    /// Returns the offset of the data page ID in a schema page; the next schema page ID follows it.
+    fn schema_pointers_offset(page: &Page) -> Result<usize, String> {
+        let mut offset = 0;
+        let name = Self::read_string(page, &mut offset).ok_or("Failed to read table name")?;
+        if name.is_empty() || offset + 4 > page.data.len() {
+            return Err("Invalid schema page".to_string());
+        }
+        let num_cols = u32::from_le_bytes(page.data[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+
+        for _ in 0..num_cols {
+            Self::read_string(page, &mut offset).ok_or("Failed to read column name")?;
+            Self::read_string(page, &mut offset).ok_or("Failed to read data type")?;
+        }
+        if offset + 16 > page.data.len() {
+            return Err("Invalid schema page".to_string());
+        }
+        Ok(offset)
+    }
+
+    /// Rewrites the schema page and data pages of `old` to hold `table`, keeping its place in
+    /// the schema chain. Used when the definition of a table changes.
+    pub fn replace_table(&mut self, old: &Table, table: &Table) -> Result<(), String> {
+        let schema_page_id = self.find_table_schema_page(&old.name)?
+            .ok_or_else(|| format!("Table '{}' not found in the schema chain", old.name))?;
+        let old_page = self.storage.read_page(schema_page_id);
+        let pointers = Self::schema_pointers_offset(&old_page)?;
+        let old_data_page_id = u64::from_le_bytes(old_page.data[pointers..pointers + 8].try_into().unwrap());
+        let next_page = &old_page.data[pointers + 8..pointers + 16];
+
+        // The old rows are laid out by the old columns, so the chain is freed and written anew
+        for page_id in self.data_page_ids(old_data_page_id, &old.columns)? {
+            self.free_page(page_id);
+        }
+        let data_page = self.save_rows_to_pages(&table.rows, &table.columns, None)?;
+
+        let mut page = Page::new(schema_page_id);
+        let offset = Self::write_schema_header(table, &mut page)?;
+        if offset + 16 > page.data.len() {
+            return Err("Page overflow".to_string());
+        }
+        page.data[offset..offset + 8].copy_from_slice(&data_page.id.to_le_bytes());
+        page.data[offset + 8..offset + 16].copy_from_slice(next_page);
+        Self::write_schema_extensions(table, &mut page, offset + 16)?;
+
+        self.storage.write_page(&page);
+        Ok(())
+    }
+
+    /// Removes a table from the schema chain and frees its schema and data pages.
+    pub fn drop_table(&mut self, table: &Table) -> Result<(), String> {
+        let mut header = self.storage.read_page(HEADER_PAGE_ID);
+        let mut previous: Option<(Page, usize)> = None;
+        let mut current_page_id = u64::from_le_bytes(header.data[12..20].try_into().unwrap());
+        let mut pages_visited = std::collections::HashSet::new();
+
+        while current_page_id != 0 && pages_visited.insert(current_page_id) {
+            let page = self.storage.read_page(current_page_id);
+            let pointers = Self::schema_pointers_offset(&page)?;
+            let mut name_offset = 0;
+            let name = Self::read_string(&page, &mut name_offset).unwrap_or_default();
+            let next_page_id = u64::from_le_bytes(page.data[pointers + 8..pointers + 16].try_into().unwrap());
+
+            if name != table.name {
+                previous = Some((page, pointers));
+                current_page_id = next_page_id;
+                continue;
+            }
+
+            // Unlink the schema page; the first table is pointed to by the header
+            match previous {
+                Some((mut previous_page, previous_pointers)) => {
+                    previous_page.data[previous_pointers + 8..previous_pointers + 16]
+                        .copy_from_slice(&next_page_id.to_le_bytes());
+                    self.storage.write_page(&previous_page);
+                }
+                None => header.data[12..20].copy_from_slice(&next_page_id.to_le_bytes()),
+            }
+            let table_count = u32::from_le_bytes(header.data[20..24].try_into().unwrap());
+            header.data[20..24].copy_from_slice(&table_count.saturating_sub(1).to_le_bytes());
+            self.storage.write_page(&header);
+
+            let data_page_id = u64::from_le_bytes(page.data[pointers..pointers + 8].try_into().unwrap());
+            for data_page in self.data_page_ids(data_page_id, &table.columns)? {
+                self.free_page(data_page);
+            }
+            self.free_page(current_page_id);
+            return Ok(());
+        }
+
+        Err(format!("Table '{}' not found in the schema chain", table.name))
+    }
+
     fn read_table_from_page(&mut self, page_id: u64) -> Result<Option<(Table, u64)>, String> {
         let page = self.storage.read_page(page_id);
 
@@ -237,13 +499,19 @@ impl Database {
             if offset + type_len > page.data.len() {
                 return Ok(None);
             }
-            let data_type = String::from_utf8(page.data[offset..offset + type_len].to_vec())
+            let type_str = String::from_utf8(page.data[offset..offset + type_len].to_vec())
                 .map_err(|_| "Invalid data type encoding")?;
             offset += type_len;
 
+            // Types are stored as written, e.g. "VARCHAR(255)"; older files may hold
+            // arbitrary strings, which are kept verbatim.
+            let (data_type, type_params) = parse_data_type(&type_str).unwrap_or((type_str, Vec::new()));
+
             columns.push(Column {
                 name: col_name,
                 data_type,
+                type_params,
+                ..Default::default()
             });
         }
 
@@ -267,6 +535,9 @@ impl Database {
                 .try_into()
                 .map_err(|_| "Failed to read next page ID")?,
         );
+        offset += 8;
+
+        let constraints = Self::read_schema_extensions(&page, offset, &mut columns);
 
         // Load rows from data pages
         let rows = if data_page_id > 0 {
@@ -279,18 +550,271 @@ impl Database {
             Table {
                 name,
                 columns,
+                constraints,
                 rows,
             },
             next_page,
         )))
     }
 
+    /// Reads the column attributes stored after the next page pointer of a schema page,
+    /// returning the table constraints.
+    fn read_schema_extensions(page: &Page, mut offset: usize, columns: &mut [Column]) -> Vec<TableConstraint> {
+        let mut constraints = Vec::new();
+        let parser = Parser::new();
+
+        for column in columns.iter_mut() {
+            if offset + 1 > page.data.len() {
+                return constraints;
+            }
+            let flags = page.data[offset];
+            offset += 1;
+
+            column.primary_key = flags & COLUMN_PRIMARY_KEY != 0;
+            column.not_null = flags & COLUMN_NOT_NULL != 0;
+            column.unique = flags & COLUMN_UNIQUE != 0;
+            column.auto_increment = flags & COLUMN_AUTO_INCREMENT != 0;
+        }
+
+        for column in columns.iter_mut() {
+            if offset + 4 > page.data.len() {
+                return constraints;
+            }
+            let default_len = u32::from_le_bytes(page.data[offset..offset + 4].try_into().unwrap()) as usize;
+            offset += 4;
+
+            if default_len == 0 || offset + default_len > page.data.len() {
+                continue;
+            }
+            if let Ok(default) = String::from_utf8(page.data[offset..offset + default_len].to_vec()) {
+                // A stored default the grammar does not accept is used as written
+                column.default = Some(parser.parse_expression(&default)
+                    .unwrap_or_else(|| Expr::Literal(Literal::String(unquote(&default).to_string()))));
+            }
+            offset += default_len;
+        }
+
+        if offset + 4 > page.data.len() {
+            return constraints;
+        }
+        let num_constraints = u32::from_le_bytes(page.data[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+
+        for _ in 0..num_constraints {
+            if offset + 1 > page.data.len() {
+                break;
+            }
+            let kind = page.data[offset];
+            offset += 1;
+
+            match kind {
+                CONSTRAINT_CHECK => {
+                    let expression = match Self::read_string(page, &mut offset) {
+                        Some(expression) => expression,
+                        None => break,
+                    };
+                    match parser.parse_check(&expression) {
+                        Some(check) => constraints.push(check),
+                        None => eprintln!("Warning: Could not parse stored CHECK ({})", expression),
+                    }
+                }
+                CONSTRAINT_FOREIGN_KEY => {
+                    match Self::read_foreign_key(page, &mut offset) {
+                        Some(foreign_key) => constraints.push(TableConstraint::ForeignKey(foreign_key)),
+                        None => break,
+                    }
+                }
+                _ => {
+                    eprintln!("Warning: Unknown constraint kind {} in schema page {}", kind, page.id);
+                    break;
+                }
+            }
+        }
+
+        constraints
+    }
+
+    /// Writes the column attributes of `table` into a schema page, starting at `offset`.
+    fn write_schema_extensions(table: &Table, page: &mut Page, mut offset: usize) -> Result<(), String> {
+        for col in &table.columns {
+            if offset + 1 > page.data.len() {
+                return Err("Page overflow".to_string());
+            }
+            let mut flags = 0;
+            if col.primary_key {
+                flags |= COLUMN_PRIMARY_KEY;
+            }
+            if col.not_null {
+                flags |= COLUMN_NOT_NULL;
+            }
+            if col.unique {
+                flags |= COLUMN_UNIQUE;
+            }
+            if col.auto_increment {
+                flags |= COLUMN_AUTO_INCREMENT;
+            }
+            page.data[offset] = flags;
+            offset += 1;
+        }
+
+        for col in &table.columns {
+            let default = col.default.as_ref().map_or_else(String::new, Expr::to_string);
+            let default_bytes = default.as_bytes();
+            if offset + 4 + default_bytes.len() > page.data.len() {
+                return Err("Column default too long".to_string());
+            }
+            page.data[offset..offset + 4].copy_from_slice(&(default_bytes.len() as u32).to_le_bytes());
+            offset += 4;
+            page.data[offset..offset + default_bytes.len()].copy_from_slice(default_bytes);
+            offset += default_bytes.len();
+        }
+
+        if offset + 4 > page.data.len() {
+            return Err("Page overflow".to_string());
+        }
+        page.data[offset..offset + 4].copy_from_slice(&(table.constraints.len() as u32).to_le_bytes());
+        offset += 4;
+
+        for constraint in &table.constraints {
+            match constraint {
+                TableConstraint::Check { expression, .. } => {
+                    if offset + 1 > page.data.len() {
+                        return Err("Page overflow".to_string());
+                    }
+                    page.data[offset] = CONSTRAINT_CHECK;
+                    offset += 1;
+                    Self::write_string(page, &mut offset, expression)?;
+                }
+                TableConstraint::ForeignKey(foreign_key) => {
+                    if offset + 1 > page.data.len() {
+                        return Err("Page overflow".to_string());
+                    }
+                    page.data[offset] = CONSTRAINT_FOREIGN_KEY;
+                    offset += 1;
+                    Self::write_foreign_key(page, &mut offset, foreign_key)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a length-prefixed (u32) UTF-8 string, advancing `offset` past it.
+    fn read_string(page: &Page, offset: &mut usize) -> Option<String> {
+        if *offset + 4 > page.data.len() {
+            return None;
+        }
+        let len = u32::from_le_bytes(page.data[*offset..*offset + 4].try_into().ok()?) as usize;
+        *offset += 4;
+
+        if *offset + len > page.data.len() {
+            return None;
+        }
+        let value = String::from_utf8(page.data[*offset..*offset + len].to_vec()).ok()?;
+        *offset += len;
+        Some(value)
+    }
+
+    /// Writes a length-prefixed (u32) string, advancing `offset` past it.
+    fn write_string(page: &mut Page, offset: &mut usize, value: &str) -> Result<(), String> {
+        let bytes = value.as_bytes();
+        if *offset + 4 + bytes.len() > page.data.len() {
+            return Err("Page overflow".to_string());
+        }
+        page.data[*offset..*offset + 4].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+        *offset += 4;
+        page.data[*offset..*offset + bytes.len()].copy_from_slice(bytes);
+        *offset += bytes.len();
+        Ok(())
+    }
+
+    fn read_string_list(page: &Page, offset: &mut usize) -> Option<Vec<String>> {
+        if *offset + 4 > page.data.len() {
+            return None;
+        }
+        let count = u32::from_le_bytes(page.data[*offset..*offset + 4].try_into().ok()?);
+        *offset += 4;
+        (0..count).map(|_| Self::read_string(page, offset)).collect()
+    }
+
+    fn write_string_list(page: &mut Page, offset: &mut usize, values: &[String]) -> Result<(), String> {
+        if *offset + 4 > page.data.len() {
+            return Err("Page overflow".to_string());
+        }
+        page.data[*offset..*offset + 4].copy_from_slice(&(values.len() as u32).to_le_bytes());
+        *offset += 4;
+        for value in values {
+            Self::write_string(page, offset, value)?;
+        }
+        Ok(())
+    }
+
+    fn read_foreign_key(page: &Page, offset: &mut usize) -> Option<ForeignKey> {
+        let columns = Self::read_string_list(page, offset)?;
+        let parent_table = Self::read_string(page, offset)?;
+        let parent_columns = Self::read_string_list(page, offset)?;
+
+        if *offset + 1 > page.data.len() {
+            return None;
+        }
+        let on_delete = match page.data[*offset] {
+            ACTION_RESTRICT => ReferentialAction::Restrict,
+            ACTION_CASCADE => ReferentialAction::Cascade,
+            ACTION_SET_NULL => ReferentialAction::SetNull,
+            _ => ReferentialAction::NoAction,
+        };
+        *offset += 1;
+
+        Some(ForeignKey {
+            columns,
+            parent_table,
+            parent_columns,
+            on_delete,
+        })
+    }
+
+    fn write_foreign_key(page: &mut Page, offset: &mut usize, foreign_key: &ForeignKey) -> Result<(), String> {
+        Self::write_string_list(page, offset, &foreign_key.columns)?;
+        Self::write_string(page, offset, &foreign_key.parent_table)?;
+        Self::write_string_list(page, offset, &foreign_key.parent_columns)?;
+
+        if *offset + 1 > page.data.len() {
+            return Err("Page overflow".to_string());
+        }
+        page.data[*offset] = match foreign_key.on_delete {
+            ReferentialAction::NoAction => ACTION_NO_ACTION,
+            ReferentialAction::Restrict => ACTION_RESTRICT,
+            ReferentialAction::Cascade => ACTION_CASCADE,
+            ReferentialAction::SetNull => ACTION_SET_NULL,
+        };
+        *offset += 1;
+        Ok(())
+    }
+
     fn load_rows_from_pages(
         &mut self,
         start_page_id: u64,
         columns: &[Column],
     ) -> Result<Vec<Row>, String> {
+        Ok(self.read_data_pages(start_page_id, columns)?.0)
+    }
+
+    /// Returns the IDs of the pages in a data page chain.
+    fn data_page_ids(&mut self, start_page_id: u64, columns: &[Column]) -> Result<Vec<u64>, String> {
+        if start_page_id == 0 {
+            return Ok(Vec::new());
+        }
+        Ok(self.read_data_pages(start_page_id, columns)?.1)
+    }
+
+    /// Reads a data page chain, returning its rows and the IDs of the pages it consists of.
+    fn read_data_pages(
+        &mut self,
+        start_page_id: u64,
+        columns: &[Column],
+    ) -> Result<(Vec<Row>, Vec<u64>), String> {
         let mut rows = Vec::new();
+        let mut page_ids = vec![start_page_id];
         let mut current_page_id = start_page_id;
 
         loop {
@@ -347,6 +871,17 @@ impl Database {
                             offset += 8;
                             row_values.push(int_val.to_string());
                         }
+                        TYPE_UUID => {
+                            // Read 16-byte UUID
+                            if offset + 16 > page.data.len() {
+                                break;
+                            }
+                            let bytes: [u8; 16] = page.data[offset..offset + 16]
+                                .try_into()
+                                .map_err(|_| "Failed to read UUID value")?;
+                            offset += 16;
+                            row_values.push(Uuid::from_bytes(bytes).to_string());
+                        }
                         TYPE_TEXT => {
                             // Read text length and value
                             if offset + 4 > page.data.len() {
@@ -415,13 +950,14 @@ impl Database {
                     .map_err(|_| "Failed to read next page ID")?,
             );
 
-            if next_page == 0 {
+            if next_page == 0 || page_ids.contains(&next_page) {
                 break;
             }
+            page_ids.push(next_page);
             current_page_id = next_page;
         }
 
-        Ok(rows)
+        Ok((rows, page_ids))
     }
 
     fn find_table_schema_page(&mut self, table_name: &str) -> Result<Option<u64>, String> {
@@ -465,7 +1001,7 @@ impl Database {
             let name = String::from_utf8(page.data[offset..offset + name_len].to_vec())
                 .map_err(|_| "Invalid table name encoding")?;
 
-            if name.to_lowercase() == table_name.to_lowercase() {
+            if name == table_name {
                 return Ok(Some(current_page_id));
             }
 
@@ -537,10 +1073,9 @@ impl Database {
         Ok(None)
     }
 
-    pub fn save_table(&mut self, table: &Table, is_new: bool) -> Result<(), String> {
-        // Save the table schema and data to pages
-        let schema_page = self.storage.allocate_page();
-        let mut page = Page::new(schema_page.id);
+    /// Writes the name and columns of `table` at the start of a schema page, returning the
+    /// offset at which the data page ID goes.
+    fn write_schema_header(table: &Table, page: &mut Page) -> Result<usize, String> {
         let mut offset = 0;
 
         // Write table name
@@ -572,7 +1107,8 @@ impl Database {
             page.data[offset..offset + col_name_bytes.len()].copy_from_slice(col_name_bytes);
             offset += col_name_bytes.len();
 
-            let type_bytes = col.data_type.as_bytes();
+            let type_name = col.type_name();
+            let type_bytes = type_name.as_bytes();
             if offset + 4 + type_bytes.len() > page.data.len() {
                 return Err("Data type too long".to_string());
             }
@@ -582,11 +1118,20 @@ impl Database {
             offset += type_bytes.len();
         }
 
+        Ok(offset)
+    }
+
+    pub fn save_table(&mut self, table: &Table, is_new: bool) -> Result<(), String> {
+        // Save the table schema and data to pages
+        let schema_page = self.allocate_page();
+        let mut page = Page::new(schema_page.id);
+        let mut offset = Self::write_schema_header(table, &mut page)?;
+
         // Allocate data page for rows
         let data_page = if !table.rows.is_empty() {
             self.save_rows_to_pages(&table.rows, &table.columns, None)?
         } else {
-            self.storage.allocate_page()
+            self.allocate_page()
         };
 
         // Write data page ID
@@ -596,6 +1141,9 @@ impl Database {
         page.data[offset..offset + 8].copy_from_slice(&data_page.id.to_le_bytes());
         offset += 8;
 
+        // Constraints and other column attributes follow the next page pointer
+        Self::write_schema_extensions(table, &mut page, offset + 8)?;
+
         // If this is not a new table, we need to update the existing schema chain
         if !is_new {
             // For now, we'll just save the table with no next page
@@ -697,7 +1245,7 @@ impl Database {
         let page_id = if let Some(id) = start_page_id {
             id
         } else {
-            self.storage.allocate_page().id
+            self.allocate_page().id
         };
 
         let mut page = Page::new(page_id);
@@ -714,8 +1262,9 @@ impl Database {
         // Write rows
         for row in rows {
             let row_start_offset = offset;
+            let mut values_written = 0;
 
-            // Try to write the row
+            // Try to write the row; a value that does not fit stops the loop early
             for (value, col) in row.values.iter().zip(columns.iter()) {
                 let col_type = col.data_type.to_uppercase();
 
@@ -727,7 +1276,34 @@ impl Database {
                 if value.is_empty() {
                     page.data[offset] = TYPE_NULL;
                     offset += 1;
-                } else if col_type == "INT" || col_type == "INTEGER" {
+                } else if col_type == "UUID" {
+                    // Parse and write as 16-byte UUID
+                    match Uuid::parse_str(value) {
+                        Ok(uuid) => {
+                            page.data[offset] = TYPE_UUID;
+                            offset += 1;
+                            if offset + 16 > page.data.len() {
+                                break;
+                            }
+                            page.data[offset..offset + 16].copy_from_slice(uuid.as_bytes());
+                            offset += 16;
+                        }
+                        Err(_) => {
+                            // Fallback to text if parsing fails
+                            page.data[offset] = TYPE_TEXT;
+                            offset += 1;
+                            let val_bytes = value.as_bytes();
+                            if offset + 4 + val_bytes.len() > page.data.len() {
+                                break;
+                            }
+                            page.data[offset..offset + 4]
+                                .copy_from_slice(&(val_bytes.len() as u32).to_le_bytes());
+                            offset += 4;
+                            page.data[offset..offset + val_bytes.len()].copy_from_slice(val_bytes);
+                            offset += val_bytes.len();
+                        }
+                    }
+                } else if col_type == "INTEGER" {
                     // Parse and write as integer
                     match value.parse::<i64>() {
                         Ok(int_val) => {
@@ -768,10 +1344,11 @@ impl Database {
                     page.data[offset..offset + val_bytes.len()].copy_from_slice(val_bytes);
                     offset += val_bytes.len();
                 }
+                values_written += 1;
             }
 
             // Check if we successfully wrote the entire row
-            if row.values.len() == columns.len() && offset <= page.data.len() - 8 {
+            if values_written == columns.len() && row.values.len() == columns.len() && offset <= page.data.len() - 8 {
                 rows_written += 1;
             } else {
                 // Row didn't fit, rollback
@@ -850,6 +1427,14 @@ impl Database {
                     .map_err(|_| "Failed to read data page ID")?,
             );
             
+            // The rows are rewritten from the first page on; the rest of the old chain is freed
+            // so the rewrite can reuse it
+            if existing_data_page_id > 0 {
+                for page_id in self.data_page_ids(existing_data_page_id, &table.columns)?.into_iter().skip(1) {
+                    self.free_page(page_id);
+                }
+            }
+
             // Update data pages, reusing the first page if possible
             let first_data_page = if existing_data_page_id > 0 {
                 self.save_rows_to_pages(&table.rows, &table.columns, Some(existing_data_page_id))?
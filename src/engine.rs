@@ -1,31 +1,108 @@
-use crate::parser::{Column, WhereClause};
+use crate::parser::{
+    identifier_matches, identifier_name, quote_identifier, AlterAction, ArithmeticOp, Column, ComparisonOp, Expr, ForeignKey, JsonPath, JsonPathStep, Literal,
+    Parser, Predicate, ReferentialAction, SelectItem, SortKey, TableConstraint, WhereClause,
+};
+use crate::sort::external_sort;
 use regex::Regex;
 use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Table {
     pub name: String,
     pub columns: Vec<Column>,
+    pub constraints: Vec<TableConstraint>,
     pub rows: Vec<Row>,
 }
 
+impl Table {
+    /// Returns the foreign keys declared on this table.
+    pub fn foreign_keys(&self) -> impl Iterator<Item = &ForeignKey> {
+        self.constraints.iter().filter_map(|c| match c {
+            TableConstraint::ForeignKey(foreign_key) => Some(foreign_key),
+            _ => None,
+        })
+    }
+
+    /// Returns the index of the column `name` refers to.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        find_named(&self.columns, name, |c| &c.name)
+    }
+
+    /// Returns the indices of the named columns, failing on the first unknown name.
+    pub fn column_indices(&self, names: &[String]) -> Result<Vec<usize>, String> {
+        names.iter().map(|name| {
+            self.column_index(name)
+                .ok_or_else(|| format!("Column '{}' not found in table '{}'", name, self.name))
+        }).collect()
+    }
+
+    /// Returns the indices of columns by the names they are stored under, such as the resolved
+    /// columns of a foreign key, failing on the first unknown name.
+    pub fn stored_column_indices(&self, names: &[String]) -> Result<Vec<usize>, String> {
+        names.iter().map(|name| {
+            self.columns.iter().position(|c| c.name == *name)
+                .ok_or_else(|| format!("Column '{}' not found in table '{}'", name, self.name))
+        }).collect()
+    }
+
+    /// Returns the indices of the columns making up the primary key, in column order.
+    pub fn primary_key_indices(&self) -> Vec<usize> {
+        self.columns.iter().enumerate()
+            .filter(|(_, c)| c.primary_key)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Row {
     pub values: Vec<String>,
 }
 
+/// A named counter handing out increasing values through `nextval('name')`.
+#[derive(Debug, Clone)]
+pub struct Sequence {
+    pub name: String,
+    pub increment: i64,
+    /// The value handed out most recently; a new sequence starts one increment before its start.
+    pub last_value: i64,
+}
+
+impl Sequence {
+    /// Whether `value` lies beyond the value handed out most recently, in the direction the
+    /// sequence counts.
+    fn is_past(&self, value: i64) -> bool {
+        if self.increment > 0 { value > self.last_value } else { value < self.last_value }
+    }
+}
+
 pub struct Catalog {
     tables: Vec<Table>,
+    sequences: Vec<Sequence>,
+    /// The unique keys of tables written to since they were last changed otherwise, by table name.
+    unique_keys: HashMap<String, UniqueKeys>,
+}
+
+impl Default for Catalog {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Catalog {
     pub fn new() -> Self {
         Catalog {
             tables: Vec::new(),
+            sequences: Vec::new(),
+            unique_keys: HashMap::new(),
         }
     }
 
-    pub fn create_table(&mut self, name: String, columns: Vec<Column>) -> Result<(), String> {
+    pub fn create_table(&mut self, name: String, columns: Vec<Column>, constraints: Vec<TableConstraint>) -> Result<(), String> {
         // Check if table already exists
         if self.tables.iter().any(|t| t.name == name) {
             return Err(format!("Table '{}' already exists", name));
@@ -34,18 +111,23 @@ impl Catalog {
         let table = Table {
             name,
             columns,
+            constraints,
             rows: Vec::new(),
         };
+        self.unique_keys.remove(&table.name);
         self.tables.push(table);
         Ok(())
     }
 
+    /// Finds a table to change. Its cached unique keys are dropped, as the change may make them stale.
     pub fn find_table_mut(&mut self, name: &str) -> Option<&mut Table> {
-        self.tables.iter_mut().find(|t| t.name.to_lowercase() == name.to_lowercase())
+        let index = find_named(&self.tables, name, |t| &t.name)?;
+        self.unique_keys.remove(&self.tables[index].name);
+        Some(&mut self.tables[index])
     }
 
     pub fn find_table(&self, name: &str) -> Option<&Table> {
-        self.tables.iter().find(|t| t.name.to_lowercase() == name.to_lowercase())
+        find_named(&self.tables, name, |t| &t.name).map(|i| &self.tables[i])
     }
 
     pub fn list_tables(&self) -> Vec<&str> {
@@ -59,21 +141,445 @@ impl Catalog {
     pub fn load_tables(&mut self, tables: Vec<Table>) {
         for table in tables {
             if !self.tables.iter().any(|t| t.name == table.name) {
+                self.unique_keys.remove(&table.name);
                 self.tables.push(table);
             }
         }
     }
     
+    pub fn remove_table(&mut self, name: &str) -> Option<Table> {
+        let index = find_named(&self.tables, name, |t| &t.name)?;
+        let table = self.tables.remove(index);
+        self.unique_keys.remove(&table.name);
+        Some(table)
+    }
+
+    pub fn remove_sequence(&mut self, name: &str) -> Option<Sequence> {
+        let index = find_named(&self.sequences, name, |s| &s.name)?;
+        Some(self.sequences.remove(index))
+    }
+
+    pub fn create_sequence(&mut self, sequence: Sequence) -> Result<(), String> {
+        if self.sequences.iter().any(|s| s.name == sequence.name) {
+            return Err(format!("Sequence '{}' already exists", sequence.name));
+        }
+        self.sequences.push(sequence);
+        Ok(())
+    }
+
+    pub fn find_sequence(&self, name: &str) -> Option<&Sequence> {
+        find_named(&self.sequences, name, |s| &s.name).map(|i| &self.sequences[i])
+    }
+
+    pub fn find_sequence_mut(&mut self, name: &str) -> Option<&mut Sequence> {
+        let index = find_named(&self.sequences, name, |s| &s.name)?;
+        Some(&mut self.sequences[index])
+    }
+
+    pub fn get_all_sequences(&self) -> &Vec<Sequence> {
+        &self.sequences
+    }
+
+    pub fn load_sequences(&mut self, sequences: Vec<Sequence>) {
+        self.sequences = sequences;
+    }
+
     pub fn add_table(&mut self, table: Table) {
-        if !self.tables.iter().any(|t| t.name.to_lowercase() == table.name.to_lowercase()) {
+        if !self.tables.iter().any(|t| t.name == table.name) {
+            self.unique_keys.remove(&table.name);
             self.tables.push(table);
         }
     }
+
+    /// Takes the unique keys of the table stored as `name` out of the cache, collecting them
+    /// from its rows when they are not cached.
+    fn take_unique_keys(&mut self, name: &str) -> Result<UniqueKeys, String> {
+        if let Some(keys) = self.unique_keys.remove(name) {
+            return Ok(keys);
+        }
+        let table = self.tables.iter().find(|t| t.name == name)
+            .ok_or_else(|| format!("Table '{}' does not exist", name))?;
+        UniqueKeys::collect(table, &table.rows)
+    }
+
+    /// Caches `keys` as the unique keys of the table stored as `name`; they must match its rows.
+    fn store_unique_keys(&mut self, name: &str, keys: UniqueKeys) {
+        self.unique_keys.insert(name.to_string(), keys);
+    }
+}
+
+/// The keys the rows of a table hold in its primary key and in each UNIQUE column. They are
+/// cached between writes, so that a write only checks the rows it adds against them.
+struct UniqueKeys {
+    sets: Vec<KeySet>,
+}
+
+/// The keys held in one unique column set. Keys containing a NULL never collide and are left out.
+struct KeySet {
+    indices: Vec<usize>,
+    constraint: &'static str,
+    keys: HashSet<Vec<String>>,
+}
+
+impl KeySet {
+    fn key(&self, table: &Table, row: &Row) -> Option<Vec<String>> {
+        let key = QueryEngine::key_of(&table.columns, &self.indices, row);
+        (!key.iter().any(|v| v.is_empty())).then_some(key)
+    }
+}
+
+impl UniqueKeys {
+    /// Collects the keys of `rows`, the complete contents of `table`, failing if two rows share one.
+    fn collect(table: &Table, rows: &[Row]) -> Result<Self, String> {
+        let mut sets: Vec<KeySet> = table.columns.iter().enumerate()
+            .filter(|(_, column)| column.unique)
+            .map(|(i, _)| KeySet { indices: vec![i], constraint: "unique key", keys: HashSet::new() })
+            .collect();
+        let primary_key = table.primary_key_indices();
+        if !primary_key.is_empty() {
+            sets.push(KeySet { indices: primary_key, constraint: "primary key", keys: HashSet::new() });
+        }
+        let mut keys = UniqueKeys { sets };
+        keys.check(table, &[], rows)?;
+        keys.apply(table, &[], rows);
+        Ok(keys)
+    }
+
+    /// Checks that replacing the rows `removed` of `table` by `added` leaves no key held twice.
+    fn check(&self, table: &Table, removed: &[Row], added: &[Row]) -> Result<(), String> {
+        for set in &self.sets {
+            let freed: HashSet<Vec<String>> = removed.iter().filter_map(|row| set.key(table, row)).collect();
+            let mut seen = HashSet::new();
+            for row in added {
+                let Some(key) = set.key(table, row) else { continue };
+                if (set.keys.contains(&key) && !freed.contains(&key)) || !seen.insert(key.clone()) {
+                    let names: Vec<&str> = set.indices.iter().map(|&i| table.columns[i].name.as_str()).collect();
+                    return Err(format!(
+                        "Duplicate {} ({})=({}) in table '{}'",
+                        set.constraint,
+                        names.join(", "),
+                        key.join(", "),
+                        table.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the keys of the rows `removed` by those of `added`, once `check` has passed.
+    fn apply(&mut self, table: &Table, removed: &[Row], added: &[Row]) {
+        for set in &mut self.sets {
+            for row in removed {
+                if let Some(key) = set.key(table, row) {
+                    set.keys.remove(&key);
+                }
+            }
+            for row in added {
+                if let Some(key) = set.key(table, row) {
+                    set.keys.insert(key);
+                }
+            }
+        }
+    }
+}
+
+/// A column reference resolved against a table, optionally drilling into a JSON document.
+struct ColumnOperand {
+    index: usize,
+    data_type: String,
+    json_path: Option<JsonPath>,
+}
+
+impl ColumnOperand {
+    /// Resolves `expr`, either a plain column name or a JSON path such as `doc->>'name'`.
+    fn resolve(table: &Table, expr: &Expr) -> Result<Self, String> {
+        let (name, json_path) = match expr {
+            Expr::Identifier(name) => (name, None),
+            Expr::JsonPath { column, path } => (column, Some(path.clone())),
+            _ => return Err(format!("'{}' is not a column", expr)),
+        };
+        let index = table.column_index(name)
+            .ok_or_else(|| format!("Column '{}' not found in table '{}'", name, table.name))?;
+        let column = &table.columns[index];
+        if json_path.is_some() && column.data_type != "JSON" {
+            return Err(format!(
+                "Operator -> requires a JSON column, but '{}' is {}",
+                column.name, column.data_type
+            ));
+        }
+
+        Ok(ColumnOperand {
+            index,
+            data_type: column.data_type.clone(),
+            json_path,
+        })
+    }
+
+    /// Returns the operand's value in `row` together with the type it should be compared as.
+    /// Missing JSON members evaluate to an empty string, the engine's representation of NULL.
+    fn value(&self, row: &Row) -> (String, String) {
+        let raw = row.values.get(self.index).cloned().unwrap_or_default();
+        let path = match &self.json_path {
+            Some(path) => path,
+            None => return (raw, self.data_type.clone()),
+        };
+
+        match extract_json_path(&raw, path) {
+            Some(Value::Null) | None => (String::new(), "TEXT".to_string()),
+            Some(Value::String(s)) if path.as_text => (s, "TEXT".to_string()),
+            Some(Value::Number(n)) if path.as_text && n.is_i64() => (n.to_string(), "INTEGER".to_string()),
+            Some(Value::Number(n)) if path.as_text => (n.to_string(), "REAL".to_string()),
+            Some(value) => (value.to_string(), if path.as_text { "TEXT" } else { "JSON" }.to_string()),
+        }
+    }
+}
+
+/// Returns the index of the item `reference` refers to. An unquoted reference is folded to lower
+/// case and an exact match for that is preferred, so that with both `Users` and `users` present,
+/// `users` and `USERS` alike refer to the latter, however the reference is spelled.
+fn find_named<T>(items: &[T], reference: &str, name: impl Fn(&T) -> &str) -> Option<usize> {
+    let folded = (!reference.starts_with('"')).then(|| reference.to_lowercase());
+    folded.and_then(|folded| items.iter().position(|item| name(item) == folded))
+        .or_else(|| items.iter().position(|item| identifier_matches(name(item), reference)))
+}
+
+/// Describes a constraint in error messages, e.g. `CHECK (age >= 0)`.
+fn describe_constraint(constraint: &TableConstraint) -> String {
+    match constraint {
+        TableConstraint::Check { expression, .. } => format!("CHECK ({})", expression),
+        TableConstraint::ForeignKey(foreign_key) => foreign_key.to_string(),
+    }
+}
+
+/// Renames `from` to `to` in `names`, returning whether it was there.
+fn rename_in_list(names: &mut [String], from: &str, to: &str) -> bool {
+    let mut changed = false;
+    for name in names.iter_mut().filter(|n| *n == from) {
+        *name = to.to_string();
+        changed = true;
+    }
+    changed
+}
+
+/// Returns the sequence name of a `nextval('name')` call, or `None` for any other value.
+fn nextval_argument(value: &Expr) -> Option<&str> {
+    match value {
+        Expr::Function { name, args } if name.eq_ignore_ascii_case("nextval") => match args.as_slice() {
+            [Expr::Literal(Literal::String(sequence)) | Expr::Identifier(sequence)] => Some(sequence),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the call `nextval('name')` drawing from the sequence stored as `name`.
+fn nextval_call(name: &str) -> Expr {
+    Expr::Function {
+        name: "nextval".to_string(),
+        args: vec![Expr::Literal(Literal::String(name.to_string()))],
+    }
+}
+
+/// Orders TEXT values the way the engine compares them: ignoring case, so that `=` and the
+/// ordering operators agree, and otherwise by Unicode code point.
+fn compare_text(left: &str, right: &str) -> Ordering {
+    left.chars()
+        .flat_map(char::to_lowercase)
+        .cmp(right.chars().flat_map(char::to_lowercase))
+}
+
+/// AND in SQL's three-valued logic, where `None` is unknown: false if either operand is false,
+/// unknown if either is unknown, and true otherwise.
+fn logical_and(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+/// OR in SQL's three-valued logic: true if either operand is true, unknown if either is unknown,
+/// and false otherwise.
+fn logical_or(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+    }
+}
+
+/// A value computed from an expression, with the type it compares as. Literals have no type of
+/// their own and take the type of the value they are compared with.
+struct TypedValue {
+    value: String,
+    data_type: Option<String>,
+}
+
+impl TypedValue {
+    fn untyped(value: &str) -> Self {
+        TypedValue { value: value.to_string(), data_type: None }
+    }
+
+    fn typed(value: String, data_type: &str) -> Self {
+        TypedValue { value, data_type: Some(data_type.to_string()) }
+    }
+}
+
+/// Functions computed from their arguments, which may refer to columns.
+const SCALAR_FUNCTIONS: [&str; 3] = ["UPPER", "LOWER", "LENGTH"];
+
+fn is_scalar_function(name: &str) -> bool {
+    SCALAR_FUNCTIONS.iter().any(|f| f.eq_ignore_ascii_case(name))
+}
+
+/// Applies one of `SCALAR_FUNCTIONS` to its evaluated arguments. A NULL argument gives NULL.
+fn scalar_function(name: &str, args: &[TypedValue]) -> Result<TypedValue, String> {
+    let [arg] = args else {
+        return Err(format!("Function '{}' takes 1 argument", name));
+    };
+    if arg.value.is_empty() {
+        return Ok(TypedValue::untyped(""));
+    }
+    Ok(match name.to_uppercase().as_str() {
+        "UPPER" => TypedValue::typed(arg.value.to_uppercase(), "TEXT"),
+        "LOWER" => TypedValue::typed(arg.value.to_lowercase(), "TEXT"),
+        _ => TypedValue::typed(arg.value.chars().count().to_string(), "INTEGER"),
+    })
+}
+
+/// Returns the `index`th value of a sort record, which holds each value followed by its type.
+fn typed_field(record: &[String], index: usize) -> TypedValue {
+    let data_type = &record[2 * index + 1];
+    TypedValue {
+        value: record[2 * index].clone(),
+        data_type: (!data_type.is_empty()).then(|| data_type.clone()),
+    }
+}
+
+/// Orders two values of an ORDER BY key: numerically for INTEGER and REAL, by UUID for UUIDs,
+/// and otherwise the way TEXT is compared. NULLs are placed according to `key` either way.
+fn compare_sort_values(a: &TypedValue, b: &TypedValue, key: &SortKey) -> Ordering {
+    let null_ordering = if key.nulls_first { Ordering::Less } else { Ordering::Greater };
+    let ordering = match (a.value.is_empty(), b.value.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return null_ordering,
+        (false, true) => return null_ordering.reverse(),
+        (false, false) => match comparison_type(a, b).as_str() {
+            "INTEGER" => match (a.value.parse::<i64>(), b.value.parse::<i64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                _ => compare_text(&a.value, &b.value),
+            },
+            "REAL" => match (a.value.parse::<f64>(), b.value.parse::<f64>()) {
+                (Ok(x), Ok(y)) => x.total_cmp(&y),
+                _ => compare_text(&a.value, &b.value),
+            },
+            "UUID" => match (Uuid::parse_str(&a.value), Uuid::parse_str(&b.value)) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                _ => compare_text(&a.value, &b.value),
+            },
+            _ => compare_text(&a.value, &b.value),
+        },
+    };
+    if key.descending { ordering.reverse() } else { ordering }
+}
+
+/// A column of a SELECT result: the expression computing it and the header it is shown under.
+struct OutputColumn {
+    expr: Expr,
+    header: String,
+    aliased: bool,
+}
+
+/// Chooses the type two values are compared as: the type of the typed one when the other is a
+/// literal, and their common type when both are typed, with mixed numbers compared as REAL.
+/// Two literals compare as numbers when both are numeric and as TEXT otherwise.
+fn comparison_type(left: &TypedValue, right: &TypedValue) -> String {
+    let is_numeric = |t: &str| t == "INTEGER" || t == "REAL";
+    match (left.data_type.as_deref(), right.data_type.as_deref()) {
+        (Some(t), None) | (None, Some(t)) => t,
+        (Some(l), Some(r)) if l == r => l,
+        (Some(l), Some(r)) if is_numeric(l) && is_numeric(r) => "REAL",
+        (Some(_), Some(_)) => "TEXT",
+        (None, None) if left.value.parse::<i64>().is_ok() && right.value.parse::<i64>().is_ok() => "INTEGER",
+        (None, None) if left.value.parse::<f64>().is_ok() && right.value.parse::<f64>().is_ok() => "REAL",
+        (None, None) => "TEXT",
+    }
+    .to_string()
+}
+
+/// Applies `+`, `-`, `*`, `/` or `%`. A NULL operand gives NULL. Integers use checked 64-bit
+/// arithmetic, so `7 / 2` is 3; as soon as a REAL or a fraction is involved the result is REAL.
+fn evaluate_arithmetic(operator: ArithmeticOp, left: &TypedValue, right: &TypedValue) -> Result<TypedValue, String> {
+    if left.value.is_empty() || right.value.is_empty() {
+        return Ok(TypedValue::untyped(""));
+    }
+    let divides = matches!(operator, ArithmeticOp::Divide | ArithmeticOp::Modulo);
+
+    let is_real = |v: &TypedValue| v.data_type.as_deref() == Some("REAL");
+    if !is_real(left) && !is_real(right) {
+        if let (Ok(l), Ok(r)) = (left.value.parse::<i64>(), right.value.parse::<i64>()) {
+            if divides && r == 0 {
+                return Err("Division by zero".to_string());
+            }
+            let result = match operator {
+                ArithmeticOp::Add => l.checked_add(r),
+                ArithmeticOp::Subtract => l.checked_sub(r),
+                ArithmeticOp::Multiply => l.checked_mul(r),
+                ArithmeticOp::Divide => l.checked_div(r),
+                ArithmeticOp::Modulo => l.checked_rem(r),
+            };
+            let result = result.ok_or_else(|| format!("Integer overflow in {} {} {}", l, operator, r))?;
+            return Ok(TypedValue::typed(result.to_string(), "INTEGER"));
+        }
+    }
+
+    let number = |v: &TypedValue| {
+        v.value.parse::<f64>().ok().filter(|n| n.is_finite())
+            .ok_or_else(|| format!("Operator '{}' cannot be applied to '{}'", operator, v.value))
+    };
+    let (l, r) = (number(left)?, number(right)?);
+    if divides && r == 0.0 {
+        return Err("Division by zero".to_string());
+    }
+    let result = match operator {
+        ArithmeticOp::Add => l + r,
+        ArithmeticOp::Subtract => l - r,
+        ArithmeticOp::Multiply => l * r,
+        ArithmeticOp::Divide => l / r,
+        ArithmeticOp::Modulo => l % r,
+    };
+    if !result.is_finite() {
+        return Err(format!("Result of {} {} {} is out of range", l, operator, r));
+    }
+    Ok(TypedValue::typed(result.to_string(), "REAL"))
+}
+
+/// Follows `path` through the JSON document stored in `raw`.
+fn extract_json_path(raw: &str, path: &JsonPath) -> Option<Value> {
+    let mut value: Value = serde_json::from_str(raw).ok()?;
+    for step in &path.steps {
+        value = match step {
+            JsonPathStep::Key(key) => value.get_mut(key.as_str())?.take(),
+            JsonPathStep::Index(index) => value.get_mut(*index)?.take(),
+        };
+    }
+    Some(value)
 }
 
+/// Memory an ORDER BY may use before sorted runs are spilled to a temporary file.
+pub const DEFAULT_SORT_MEMORY: usize = 16 * 1024 * 1024;
+
 pub struct QueryEngine {
     catalog: Catalog,
     database: crate::database::Database,
+    sort_memory: usize,
+}
+
+impl Default for QueryEngine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl QueryEngine {
@@ -85,47 +591,92 @@ impl QueryEngine {
         let mut database = crate::database::Database::new(path)
             .expect("Failed to initialize database");
         
-        let catalog = database.load_catalog()
+        let mut catalog = database.load_catalog()
             .unwrap_or_else(|e| {
                 eprintln!("Warning: Failed to load catalog: {}. Starting with empty database.", e);
                 Catalog::new()
             });
+        match database.load_sequences() {
+            Ok(sequences) => catalog.load_sequences(sequences),
+            Err(e) => eprintln!("Warning: Failed to load sequences: {}", e),
+        }
 
         QueryEngine {
             catalog,
             database,
+            sort_memory: DEFAULT_SORT_MEMORY,
         }
     }
 
+    /// Sets the memory, in bytes, an ORDER BY may use before it spills to a temporary file.
+    pub fn set_sort_memory(&mut self, bytes: usize) {
+        self.sort_memory = bytes;
+    }
+
     fn evaluate_condition(
         row_value: &str,
-        operator: &str,
+        operator: ComparisonOp,
         clause_value: &str,
         column_type: &str,
     ) -> bool {
-        if column_type == "INTEGER" {
+        if column_type == "UUID" {
+            let row_val = Uuid::parse_str(row_value);
+            let clause_val = Uuid::parse_str(clause_value);
+
+            if let (Ok(row_val), Ok(clause_val)) = (row_val, clause_val) {
+                match operator {
+                    ComparisonOp::Equal => row_val == clause_val,
+                    ComparisonOp::NotEqual => row_val != clause_val,
+                    ComparisonOp::Greater => row_val > clause_val,
+                    ComparisonOp::Less => row_val < clause_val,
+                    ComparisonOp::GreaterOrEqual => row_val >= clause_val,
+                    ComparisonOp::LessOrEqual => row_val <= clause_val,
+                    _ => false,
+                }
+            } else {
+                false // Could not parse one of the values as a UUID
+            }
+        } else if column_type == "INTEGER" {
             let row_val: Result<i64, _> = row_value.parse();
             let clause_val: Result<i64, _> = clause_value.parse();
 
             if let (Ok(row_val), Ok(clause_val)) = (row_val, clause_val) {
                 match operator {
-                    "=" => row_val == clause_val,
-                    "!=" => row_val != clause_val,
-                    ">" => row_val > clause_val,
-                    "<" => row_val < clause_val,
-                    ">=" => row_val >= clause_val,
-                    "<=" => row_val <= clause_val,
+                    ComparisonOp::Equal => row_val == clause_val,
+                    ComparisonOp::NotEqual => row_val != clause_val,
+                    ComparisonOp::Greater => row_val > clause_val,
+                    ComparisonOp::Less => row_val < clause_val,
+                    ComparisonOp::GreaterOrEqual => row_val >= clause_val,
+                    ComparisonOp::LessOrEqual => row_val <= clause_val,
                     _ => false,
                 }
             } else {
                 false // Could not parse one of the values as an integer
             }
+        } else if column_type == "REAL" {
+            let (Ok(row_val), Ok(clause_val)) = (row_value.parse::<f64>(), clause_value.parse::<f64>()) else {
+                return false; // Could not parse one of the values as a number
+            };
+            match operator {
+                ComparisonOp::Equal => row_val == clause_val,
+                ComparisonOp::NotEqual => row_val != clause_val,
+                ComparisonOp::Greater => row_val > clause_val,
+                ComparisonOp::Less => row_val < clause_val,
+                ComparisonOp::GreaterOrEqual => row_val >= clause_val,
+                ComparisonOp::LessOrEqual => row_val <= clause_val,
+                _ => false,
+            }
         } else {
             // Default to TEXT comparison
+            let ordering = compare_text(row_value, clause_value);
             match operator {
-                "=" => row_value.eq_ignore_ascii_case(clause_value),
-                "!=" => !row_value.eq_ignore_ascii_case(clause_value),
-                "LIKE" => {
+                ComparisonOp::Equal => ordering == Ordering::Equal,
+                ComparisonOp::NotEqual => ordering != Ordering::Equal,
+                ComparisonOp::Greater => ordering == Ordering::Greater,
+                ComparisonOp::Less => ordering == Ordering::Less,
+                ComparisonOp::GreaterOrEqual => ordering != Ordering::Less,
+                ComparisonOp::LessOrEqual => ordering != Ordering::Greater,
+                ComparisonOp::Like => {
                     let pattern = clause_value.replace('%', ".*").replace('_', ".");
                     let re = match Regex::new(&format!("(?i)^{}$", pattern)) {
                         Ok(re) => re,
@@ -133,7 +684,7 @@ impl QueryEngine {
                     };
                     re.is_match(row_value)
                 }
-                "NOT LIKE" => {
+                ComparisonOp::NotLike => {
                     let pattern = clause_value.replace('%', ".*").replace('_', ".");
                     let re = match Regex::new(&format!("(?i)^{}$", pattern)) {
                         Ok(re) => re,
@@ -141,166 +692,1360 @@ impl QueryEngine {
                     };
                     !re.is_match(row_value)
                 }
-                // GT, LT etc. for text are not part of this implementation
-                _ => false,
             }
         }
     }
 
-    pub fn execute_create_table(&mut self, name: String, columns: Vec<Column>) -> Result<(), String> {
-        self.catalog.create_table(name.clone(), columns.clone())?;
-        
-        // Get the table we just created and save it to disk
-        let table = self.catalog.find_table(&name)
-            .ok_or_else(|| format!("Failed to find table '{}' after creation", name))?
-            .clone();
-        
-        self.database.save_table(&table, true)?;
-        Ok(())
+    /// Evaluates a predicate against a row, including `IN (...)` lists and `BETWEEN`.
+    /// As in SQL, a comparison with NULL is unknown, given as `None`.
+    fn evaluate_predicate(table: &Table, row: &Row, predicate: &Predicate) -> Result<Option<bool>, String> {
+        let (Predicate::Comparison { left, .. } | Predicate::In { expr: left, .. } | Predicate::Between { expr: left, .. }) = predicate;
+        let left = Self::evaluate_expr(table, row, left)?;
+        let compare = |operator: ComparisonOp, value: &Expr| -> Result<Option<bool>, String> {
+            let right = Self::evaluate_expr(table, row, value)?;
+            if left.value.is_empty() || right.value.is_empty() {
+                return Ok(None);
+            }
+            let data_type = comparison_type(&left, &right);
+            Ok(Some(Self::evaluate_condition(&left.value, operator, &right.value, &data_type)))
+        };
+
+        match predicate {
+            Predicate::Comparison { operator, right, .. } => compare(*operator, right),
+            Predicate::In { list, negated, .. } => {
+                // Unknown rather than false when the value is not found but the list holds a NULL
+                let mut found = Some(false);
+                for value in list {
+                    found = logical_or(found, compare(ComparisonOp::Equal, value)?);
+                }
+                Ok(if *negated { found.map(|f| !f) } else { found })
+            }
+            Predicate::Between { low, high, negated, .. } => {
+                let between = logical_and(
+                    compare(ComparisonOp::GreaterOrEqual, low)?,
+                    compare(ComparisonOp::LessOrEqual, high)?,
+                );
+                Ok(if *negated { between.map(|b| !b) } else { between })
+            }
+        }
     }
 
-    pub fn execute_insert(&mut self, table: String, values: Vec<String>) -> Result<(), String> {
-        let table_ref = self
-            .catalog
-            .find_table_mut(&table)
-            .ok_or_else(|| format!("Table '{}' does not exist", table))?;
+    /// Evaluates a condition against a row of `table` in SQL's three-valued logic, where `None`
+    /// stands for unknown.
+    fn evaluate_where(table: &Table, row: &Row, clause: &WhereClause) -> Result<Option<bool>, String> {
+        Ok(match clause {
+            WhereClause::Predicate(predicate) => Self::evaluate_predicate(table, row, predicate)?,
+            WhereClause::Not(inner) => Self::evaluate_where(table, row, inner)?.map(|v| !v),
+            WhereClause::And(left, right) => {
+                logical_and(Self::evaluate_where(table, row, left)?, Self::evaluate_where(table, row, right)?)
+            }
+            WhereClause::Or(left, right) => {
+                logical_or(Self::evaluate_where(table, row, left)?, Self::evaluate_where(table, row, right)?)
+            }
+        })
+    }
 
-        // Validate column count
-        if values.len() != table_ref.columns.len() {
-            return Err(format!(
-                "Column count mismatch: expected {}, got {}",
-                table_ref.columns.len(),
-                values.len()
-            ));
+    /// Checks that every column `clause` refers to exists in `table`. Any identifier on either
+    /// side of a comparison refers to a column; text must be quoted.
+    fn resolve_where(table: &Table, clause: &WhereClause) -> Result<(), String> {
+        for predicate in clause.predicates() {
+            for reference in predicate.operands().into_iter().flat_map(Expr::column_refs) {
+                ColumnOperand::resolve(table, reference)?;
+            }
         }
-
-        table_ref.rows.push(Row { values });
-        
-        // Save updated table to disk
-        let table_clone = table_ref.clone();
-        self.database.update_table_data(&table_clone)?;
         Ok(())
     }
 
-    pub fn execute_select(&self, table_name: String, columns: Vec<String>, where_clause: Option<WhereClause>) -> Result<(Vec<String>, Vec<Row>), String> {
-        let table = self
-            .catalog
-            .find_table(&table_name)
-            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
-
-        let mut rows = table.rows.clone();
-
-        if let Some(clause) = where_clause {
-            let column_index = table.columns.iter().position(|c| c.name.to_lowercase() == clause.column.to_lowercase());
-
-            if let Some(index) = column_index {
-                let column = &table.columns[index];
-                rows = rows.into_iter().filter(|row| {
-                    if let Some(value) = row.values.get(index) {
-                        return Self::evaluate_condition(value, &clause.operator, &clause.value, &column.data_type);
-                    }
-                    false
-                }).collect();
-            } else {
-                return Err(format!("Column '{}' not found in table '{}'", clause.column, table.name));
+    /// Evaluates an expression against a row of `table`; every identifier must name a column.
+    fn evaluate_expr(table: &Table, row: &Row, expr: &Expr) -> Result<TypedValue, String> {
+        match expr {
+            Expr::Identifier(_) | Expr::JsonPath { .. } => {
+                let (value, data_type) = ColumnOperand::resolve(table, expr)?.value(row);
+                Ok(TypedValue { value, data_type: Some(data_type) })
+            }
+            Expr::Binary { operator, left, right } => evaluate_arithmetic(
+                *operator,
+                &Self::evaluate_expr(table, row, left)?,
+                &Self::evaluate_expr(table, row, right)?,
+            ),
+            Expr::Negate(inner) => {
+                evaluate_arithmetic(ArithmeticOp::Subtract, &TypedValue::untyped("0"), &Self::evaluate_expr(table, row, inner)?)
+            }
+            Expr::Function { name, args } if is_scalar_function(name) => {
+                let args = args.iter().map(|a| Self::evaluate_expr(table, row, a)).collect::<Result<Vec<_>, _>>()?;
+                scalar_function(name, &args)
             }
+            _ => Ok(TypedValue::untyped(&Self::evaluate_value(expr)?)),
         }
+    }
 
-        let selected_columns;
-        let final_rows;
-
-        if columns.contains(&"*".to_string()) {
-            selected_columns = table.columns.iter().map(|c| c.name.clone()).collect();
-            final_rows = rows;
-        } else {
-            // Find indices for each requested column, returning a specific error for any not found.
-            let mut column_indices = Vec::new();
-            for col_name in &columns {
-                match table.columns.iter().position(|c| c.name.to_lowercase() == col_name.to_lowercase()) {
-                    Some(index) => column_indices.push(index),
-                    None => return Err(format!("Column '{}' not found in table '{}'", col_name, table.name)),
-                }
+    /// Evaluates a constant expression: a literal, a bare word, arithmetic or a built-in function
+    /// call such as `gen_random_uuid()`. NULL evaluates to the empty string.
+    fn evaluate_value(value: &Expr) -> Result<String, String> {
+        match value {
+            Expr::Literal(Literal::Null) => Ok(String::new()),
+            Expr::Literal(Literal::Number(text) | Literal::String(text)) => Ok(text.clone()),
+            Expr::Identifier(word) => Ok(identifier_name(word)),
+            Expr::JsonPath { column, .. } => Err(format!("Column '{}' cannot be used here", identifier_name(column))),
+            Expr::Function { name, args } if name.eq_ignore_ascii_case("gen_random_uuid") && args.is_empty() => {
+                Ok(Uuid::new_v4().to_string())
             }
+            Expr::Function { name, args } if is_scalar_function(name) => {
+                let args = args.iter()
+                    .map(|a| Ok(TypedValue::untyped(&Self::evaluate_value(a)?)))
+                    .collect::<Result<Vec<_>, String>>()?;
+                Ok(scalar_function(name, &args)?.value)
+            }
+            Expr::Function { name, .. } if name.eq_ignore_ascii_case("nextval") => {
+                Err("nextval() can only be used as an INSERT or UPDATE value".to_string())
+            }
+            Expr::Function { name, .. } => Err(format!("Function '{}' does not exist", name)),
+            Expr::Binary { operator, left, right } => {
+                let (left, right) = (Self::evaluate_value(left)?, Self::evaluate_value(right)?);
+                Ok(evaluate_arithmetic(*operator, &TypedValue::untyped(&left), &TypedValue::untyped(&right))?.value)
+            }
+            Expr::Negate(inner) => {
+                let value = Self::evaluate_value(inner)?;
+                Ok(evaluate_arithmetic(ArithmeticOp::Subtract, &TypedValue::untyped("0"), &TypedValue::untyped(&value))?.value)
+            }
+            Expr::Default => Err("DEFAULT can only be used as an INSERT or UPDATE value".to_string()),
+        }
+    }
 
-            selected_columns = columns.clone();
-
-            final_rows = rows.into_iter().map(|row| {
-                let selected_values = column_indices.iter().map(|&index| {
-                    row.values.get(index).cloned().unwrap_or_default()
-                }).collect();
-                Row { values: selected_values }
-            }).collect();
+    /// Evaluates a value like `evaluate_value`, additionally drawing from a sequence
+    /// for `nextval('name')`.
+    fn evaluate_value_mut(&mut self, value: &Expr) -> Result<String, String> {
+        match value {
+            Expr::Function { name, args } if name.eq_ignore_ascii_case("nextval") => match args.as_slice() {
+                [Expr::Literal(Literal::String(sequence))] => {
+                    let sequence = self.sequence_reference(sequence);
+                    self.next_value(&sequence).map(|v| v.to_string())
+                }
+                [Expr::Identifier(sequence)] => self.next_value(sequence).map(|v| v.to_string()),
+                _ => Err("nextval() takes the name of a sequence".to_string()),
+            },
+            _ => Self::evaluate_value(value),
         }
+    }
 
-        Ok((selected_columns, final_rows))
+    /// Evaluates a value that may refer to the columns of `row`, such as `n + 1` in an UPDATE.
+    /// A value without column references is evaluated like `evaluate_value_mut`.
+    fn evaluate_row_value_mut(&mut self, table: &Table, row: &Row, value: &Expr) -> Result<String, String> {
+        if value.column_refs().is_empty() {
+            return self.evaluate_value_mut(value);
+        }
+        Ok(Self::evaluate_expr(table, row, value)?.value)
     }
 
-    pub fn execute_update(&mut self, table_name: String, set_clause: (String, String), where_clause: Option<WhereClause>) -> Result<usize, String> {
-        let table = self
-            .catalog
-            .find_table_mut(&table_name)
-            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+    /// Evaluates the DEFAULT expression of `column`; columns without one default to NULL.
+    fn default_value(&mut self, column: &Column) -> Result<String, String> {
+        match &column.default {
+            Some(default) => self.evaluate_value_mut(default),
+            None => Ok(String::new()),
+        }
+    }
 
-        let (column_to_set, new_value) = set_clause;
+    /// Returns a reference to the sequence named in the text of a `nextval('name')` call. Generated
+    /// defaults hold the name a sequence is stored under, so a sequence of exactly that name is
+    /// preferred; otherwise the name is looked up like an unquoted identifier.
+    fn sequence_reference(&self, name: &str) -> String {
+        if self.catalog.get_all_sequences().iter().any(|s| s.name == name) {
+            quote_identifier(name)
+        } else {
+            name.to_string()
+        }
+    }
 
-        let column_to_set_index = table.columns.iter().position(|c| c.name.to_lowercase() == column_to_set.to_lowercase());
+    /// Advances the named sequence and returns its new value. The new high-water mark is
+    /// saved immediately, so values are never handed out twice, even across restarts or by
+    /// another connection to the same file.
+    fn next_value(&mut self, name: &str) -> Result<i64, String> {
+        self.change_sequences(|catalog| {
+            let sequence = catalog.find_sequence_mut(name)
+                .ok_or_else(|| format!("Sequence '{}' does not exist", name))?;
+            sequence.last_value = sequence.last_value.checked_add(sequence.increment)
+                .ok_or_else(|| format!("Sequence '{}' reached its limit", sequence.name))?;
+            Ok(sequence.last_value)
+        })
+    }
 
-        let set_col_idx = match column_to_set_index {
-            Some(index) => index,
-            None => return Err(format!("Column '{}' not found in table '{}'", column_to_set, table.name)),
+    /// Moves the sequence behind an auto-increment column past an explicitly inserted
+    /// `value`, so later generated values do not collide with it.
+    fn advance_sequence(&mut self, column: &Column, value: &str) -> Result<(), String> {
+        let Some(name) = column.default.as_ref().and_then(nextval_argument) else {
+            return Ok(());
         };
+        let name = self.sequence_reference(name);
+        let (Some(sequence), Ok(value)) = (self.catalog.find_sequence(&name), value.parse::<i64>()) else {
+            return Ok(());
+        };
+        if !sequence.is_past(value) {
+            return Ok(());
+        }
+        self.change_sequences(|catalog| {
+            if let Some(sequence) = catalog.find_sequence_mut(&name).filter(|s| s.is_past(value)) {
+                sequence.last_value = value;
+            }
+            Ok(())
+        })
+    }
 
-        let mut updated_count = 0;
-
-        // If there's a WHERE clause, filter by it. Otherwise, update all rows.
-        if let Some(clause) = where_clause {
-            let where_column_index = table.columns.iter().position(|c| c.name.to_lowercase() == clause.column.to_lowercase());
-
-            if let Some(where_idx) = where_column_index {
-                let column = table.columns[where_idx].clone();
-                for row in table.rows.iter_mut() {
-                    if let Some(value) = row.values.get(where_idx) {
-                        if Self::evaluate_condition(value, &clause.operator, &clause.value, &column.data_type) {
-                            if let Some(val_to_update) = row.values.get_mut(set_col_idx) {
-                                *val_to_update = new_value.clone();
-                                updated_count += 1;
-                            }
-                        }
+    /// Applies `change` to the sequences of the catalog and saves them, with the database file
+    /// locked. Every connection to the file keeps sequences of its own, and another one may have
+    /// handed out values since, so each sequence first catches up with the value stored in the file.
+    fn change_sequences<T>(&mut self, change: impl FnOnce(&mut Catalog) -> Result<T, String>) -> Result<T, String> {
+        self.database.lock()?;
+        let result = self.database.load_sequences().and_then(|stored| {
+            for stored in stored {
+                if let Some(sequence) = self.catalog.find_sequence_mut(&quote_identifier(&stored.name)) {
+                    if sequence.is_past(stored.last_value) {
+                        sequence.last_value = stored.last_value;
                     }
                 }
-            } else {
-                return Err(format!("Column '{}' not found in table '{}'", clause.column, table.name));
             }
-        } else {
-            // No WHERE clause, update all rows
-            for row in table.rows.iter_mut() {
-                if let Some(val_to_update) = row.values.get_mut(set_col_idx) {
-                    *val_to_update = new_value.clone();
-                    updated_count += 1;
-                }
+            let value = change(&mut self.catalog)?;
+            self.database.save_sequences(self.catalog.get_all_sequences())?;
+            Ok(value)
+        });
+        let unlocked = self.database.unlock();
+        let value = result?;
+        unlocked?;
+        Ok(value)
+    }
+
+    /// Saves the sequences of the catalog after they were created, renamed or dropped.
+    fn save_sequences(&mut self) -> Result<(), String> {
+        self.change_sequences(|_| Ok(()))
+    }
+
+    /// Checks that `value` is acceptable for `column` and returns it in the form it is stored in,
+    /// e.g. UUIDs are rewritten to their canonical hyphenated lower-case form.
+    fn coerce_value(value: &str, column: &Column) -> Result<String, String> {
+        if value.is_empty() {
+            return Ok(String::new());
+        }
+
+        if column.data_type == "UUID" {
+            let uuid = Uuid::parse_str(value)
+                .map_err(|_| format!("Invalid UUID for column '{}': '{}'", column.name, value))?;
+            return Ok(uuid.to_string());
+        }
+
+        if column.data_type == "JSON" {
+            serde_json::from_str::<Value>(value)
+                .map_err(|e| format!("Invalid JSON for column '{}': {}", column.name, e))?;
+        }
+
+        if let Some(max_length) = column.max_length() {
+            let length = value.chars().count();
+            if length > max_length {
+                return Err(format!(
+                    "Value too long for column '{}' of type {}: {} characters",
+                    column.name,
+                    column.type_name(),
+                    length
+                ));
             }
         }
-        
-        let table_clone = table.clone();
-        self.database.update_table_data(&table_clone)?;
 
-        Ok(updated_count)
+        Ok(value.to_string())
     }
 
-    pub fn execute_truncate_table(&mut self, table_name: String) -> Result<(), String> {
+    /// Returns the indices of the rows of `table` matching `where_clause`; all rows without one.
+    fn matching_rows(table: &Table, where_clause: Option<&WhereClause>) -> Result<Vec<usize>, String> {
+        let Some(clause) = where_clause else {
+            return Ok((0..table.rows.len()).collect());
+        };
+
+        Self::resolve_where(table, clause)?;
+        let mut matching = Vec::new();
+        for (i, row) in table.rows.iter().enumerate() {
+            if Self::evaluate_where(table, row, clause)? == Some(true) {
+                matching.push(i);
+            }
+        }
+        Ok(matching)
+    }
+
+    /// Returns the values of `row` at `indices` in a form where equal values compare equal,
+    /// e.g. INTEGER values are parsed so that `01` and `1` are the same key.
+    fn key_of(columns: &[Column], indices: &[usize], row: &Row) -> Vec<String> {
+        indices.iter().map(|&i| {
+            let value = row.values.get(i).cloned().unwrap_or_default();
+            match columns[i].data_type.as_str() {
+                "INTEGER" => value.parse::<i64>().map(|v| v.to_string()).unwrap_or(value),
+                _ => value,
+            }
+        }).collect()
+    }
+
+    /// Checks the NOT NULL, UNIQUE, PRIMARY KEY and CHECK constraints of `table` against `rows`,
+    /// the complete contents the table would have after a write.
+    fn check_constraints(table: &Table, rows: &[Row]) -> Result<(), String> {
+        Self::check_row_constraints(table, rows)?;
+        UniqueKeys::collect(table, rows)?;
+        Ok(())
+    }
+
+    /// Checks the constraints of `table` that concern each row on its own, NOT NULL and CHECK,
+    /// against `rows`.
+    fn check_row_constraints(table: &Table, rows: &[Row]) -> Result<(), String> {
+        for (i, column) in table.columns.iter().enumerate() {
+            let has_null = rows.iter().any(|row| row.values.get(i).is_none_or(|v| v.is_empty()));
+            if has_null && column.primary_key {
+                return Err(format!("Primary key column '{}' cannot be NULL", column.name));
+            }
+            if has_null && column.not_null {
+                return Err(format!("Column '{}' cannot be NULL", column.name));
+            }
+        }
+
+        for constraint in &table.constraints {
+            match constraint {
+                TableConstraint::Check { expression, condition } => {
+                    Self::resolve_where(table, condition)?;
+                    for row in rows {
+                        // As in SQL, only a condition that is false violates the check, not one
+                        // made unknown by a NULL
+                        if Self::evaluate_where(table, row, condition)? == Some(false) {
+                            return Err(format!(
+                                "Row violates CHECK ({}) on table '{}'",
+                                expression, table.name
+                            ));
+                        }
+                    }
+                }
+                // Foreign keys span tables and are checked separately by the engine
+                TableConstraint::ForeignKey(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates a foreign key declared on `table` and fills in the referenced table and
+    /// column names as they are spelled in the catalog. The referenced columns must be the
+    /// parent's primary key or a UNIQUE column.
+    fn resolve_foreign_key(&self, table: &Table, foreign_key: &mut ForeignKey) -> Result<(), String> {
+        let indices = table.column_indices(&foreign_key.columns)?;
+        foreign_key.columns = indices.iter().map(|&i| table.columns[i].name.clone()).collect();
+
+        let parent = if identifier_matches(&table.name, &foreign_key.parent_table) {
+            table
+        } else {
+            self.catalog.find_table(&foreign_key.parent_table)
+                .ok_or_else(|| format!("Referenced table '{}' does not exist", foreign_key.parent_table))?
+        };
+
+        let mut parent_indices = if foreign_key.parent_columns.is_empty() {
+            parent.primary_key_indices()
+        } else {
+            parent.column_indices(&foreign_key.parent_columns)?
+        };
+        if parent_indices.len() != foreign_key.columns.len() {
+            return Err(format!(
+                "Foreign key ({}) does not match the key of '{}'",
+                foreign_key.columns.join(", "),
+                parent.name
+            ));
+        }
+
+        foreign_key.parent_table = parent.name.clone();
+        foreign_key.parent_columns = parent_indices.iter().map(|&i| parent.columns[i].name.clone()).collect();
+
+        parent_indices.sort_unstable();
+        let is_unique = parent_indices == parent.primary_key_indices()
+            || (parent_indices.len() == 1 && parent.columns[parent_indices[0]].unique);
+        if !is_unique {
+            return Err(format!(
+                "Referenced columns ({}) of '{}' are not a primary key or UNIQUE column",
+                foreign_key.parent_columns.join(", "),
+                parent.name
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the set of keys held by `rows` in the referenced columns of `foreign_key`.
+    fn parent_keys(parent: &Table, rows: &[Row], foreign_key: &ForeignKey) -> Result<HashSet<Vec<String>>, String> {
+        let indices = parent.stored_column_indices(&foreign_key.parent_columns)?;
+        Ok(rows.iter().map(|row| Self::key_of(&parent.columns, &indices, row)).collect())
+    }
+
+    /// Returns the indices of the `child_rows` whose foreign key matches one of `keys`.
+    /// Rows with a NULL in any foreign key column reference nothing.
+    fn referencing_rows(child: &Table, child_rows: &[Row], foreign_key: &ForeignKey, keys: &HashSet<Vec<String>>) -> Result<Vec<usize>, String> {
+        let indices = child.stored_column_indices(&foreign_key.columns)?;
+        Ok(child_rows.iter().enumerate()
+            .filter(|(_, row)| {
+                let key = Self::key_of(&child.columns, &indices, row);
+                !key.iter().any(|v| v.is_empty()) && keys.contains(&key)
+            })
+            .map(|(i, _)| i)
+            .collect())
+    }
+
+    /// Checks the rows `added` to the table stored as `name`, in place of the rows `removed`,
+    /// against its cached unique keys. The keys are returned to be updated and cached again
+    /// once the write is done; a rejected write leaves them cached unchanged.
+    fn check_unique_keys(&mut self, name: &str, removed: &[Row], added: &[Row]) -> Result<UniqueKeys, String> {
+        let keys = self.catalog.take_unique_keys(name)?;
+        let table = self.catalog.find_table(&quote_identifier(name))
+            .ok_or_else(|| format!("Table '{}' does not exist", name))?;
+        match keys.check(table, removed, added) {
+            Ok(()) => Ok(keys),
+            Err(e) => {
+                self.catalog.store_unique_keys(name, keys);
+                Err(e)
+            }
+        }
+    }
+
+    /// Checks that every foreign key value in `rows`, the new contents of `table`,
+    /// refers to an existing row of the parent table.
+    fn check_foreign_keys(&self, table: &Table, rows: &[Row]) -> Result<(), String> {
+        for foreign_key in table.foreign_keys() {
+            let parent = self.catalog.find_table(&quote_identifier(&foreign_key.parent_table))
+                .ok_or_else(|| format!("Referenced table '{}' does not exist", foreign_key.parent_table))?;
+            let parent_rows = if parent.name == table.name { rows } else { &parent.rows };
+            let parent_keys = Self::parent_keys(parent, parent_rows, foreign_key)?;
+
+            let indices = table.stored_column_indices(&foreign_key.columns)?;
+            for row in rows {
+                let key = Self::key_of(&table.columns, &indices, row);
+                if !key.iter().any(|v| v.is_empty()) && !parent_keys.contains(&key) {
+                    return Err(format!(
+                        "Foreign key ({})=({}) has no matching row in '{}'",
+                        foreign_key.columns.join(", "),
+                        key.join(", "),
+                        parent.name
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that no row of another table would be left referencing a key that is no longer
+    /// present in `rows`, the new contents of `table`.
+    fn check_references_to(&self, table: &Table, rows: &[Row]) -> Result<(), String> {
+        for child in self.catalog.get_all_tables() {
+            for foreign_key in child.foreign_keys() {
+                if foreign_key.parent_table != table.name {
+                    continue;
+                }
+                let child_rows = if child.name == table.name { rows } else { &child.rows };
+                let old_keys = Self::parent_keys(table, &table.rows, foreign_key)?;
+                let new_keys = Self::parent_keys(table, rows, foreign_key)?;
+                let removed_keys: HashSet<Vec<String>> = old_keys.difference(&new_keys).cloned().collect();
+
+                if !Self::referencing_rows(child, child_rows, foreign_key, &removed_keys)?.is_empty() {
+                    return Err(format!(
+                        "Cannot change '{}': rows are still referenced by foreign key {} of '{}'",
+                        table.name, foreign_key, child.name
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the rows at `indices` from `table`, applying the ON DELETE action of every
+    /// foreign key that references it. `pending` collects the new contents of each table
+    /// touched along the way; nothing is applied until the caller commits it.
+    fn delete_rows(&self, pending: &mut HashMap<String, Vec<Row>>, table: &Table, mut indices: Vec<usize>) -> Result<(), String> {
+        indices.sort_unstable();
+        indices.dedup();
+        let rows = pending.entry(table.name.clone()).or_insert_with(|| table.rows.clone());
+        let removed: Vec<Row> = indices.iter().rev().map(|&i| rows.remove(i)).collect();
+
+        for child in self.catalog.get_all_tables() {
+            for foreign_key in child.foreign_keys() {
+                if foreign_key.parent_table != table.name {
+                    continue;
+                }
+                let removed_keys = Self::parent_keys(table, &removed, foreign_key)?;
+                let child_rows = pending.entry(child.name.clone()).or_insert_with(|| child.rows.clone());
+                let referencing = Self::referencing_rows(child, child_rows, foreign_key, &removed_keys)?;
+                if referencing.is_empty() {
+                    continue;
+                }
+
+                match foreign_key.on_delete {
+                    ReferentialAction::NoAction | ReferentialAction::Restrict => {
+                        return Err(format!(
+                            "Cannot delete from '{}': rows are still referenced by foreign key {} of '{}'",
+                            table.name, foreign_key, child.name
+                        ));
+                    }
+                    ReferentialAction::SetNull => {
+                        let columns = child.stored_column_indices(&foreign_key.columns)?;
+                        for &row_index in &referencing {
+                            for &column in &columns {
+                                child_rows[row_index].values[column] = String::new();
+                            }
+                        }
+                    }
+                    ReferentialAction::Cascade => {
+                        self.delete_rows(pending, child, referencing)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates and applies the table contents collected by `delete_rows`, then saves them.
+    fn commit_pending(&mut self, pending: HashMap<String, Vec<Row>>) -> Result<(), String> {
+        for (name, rows) in &pending {
+            let table = self.catalog.find_table(&quote_identifier(name))
+                .ok_or_else(|| format!("Table '{}' does not exist", name))?;
+            Self::check_constraints(table, rows)?;
+        }
+
+        for (name, rows) in pending {
+            let table = self.catalog.find_table_mut(&quote_identifier(&name))
+                .ok_or_else(|| format!("Table '{}' does not exist", name))?;
+            table.rows = rows;
+
+            let table_clone = table.clone();
+            self.database.update_table_data(&table_clone)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the column names of a new table, as written in the statement, with the names
+    /// they are stored under, rejecting names that refer to the same column.
+    fn stored_column_names(mut columns: Vec<Column>) -> Result<Vec<Column>, String> {
+        for i in 0..columns.len() {
+            if columns[..i].iter().any(|c| identifier_matches(&c.name, &columns[i].name)) {
+                return Err(format!("Column '{}' specified more than once", columns[i].name));
+            }
+            columns[i].name = identifier_name(&columns[i].name);
+        }
+        Ok(columns)
+    }
+
+    /// Makes an auto-increment `column` of `table_name` draw from a sequence of its own and
+    /// returns that sequence, which the caller registers once the column is accepted.
+    fn serial_sequence(&self, table_name: &str, column: &mut Column) -> Result<Sequence, String> {
+        if column.data_type != "INTEGER" {
+            return Err(format!("AUTOINCREMENT column '{}' must be INTEGER", column.name));
+        }
+        let name = format!("{}_{}_seq", table_name, column.name);
+        if self.catalog.find_sequence(&quote_identifier(&name)).is_some() {
+            return Err(format!("Sequence '{}' already exists", name));
+        }
+
+        column.not_null = true;
+        column.default = Some(nextval_call(&name));
+        Ok(Sequence { name, increment: 1, last_value: 0 })
+    }
+
+    pub fn execute_create_table(&mut self, name: String, columns: Vec<Column>, constraints: Vec<TableConstraint>) -> Result<(), String> {
+        if self.catalog.find_table(&name).is_some() {
+            return Err(format!("Table '{}' already exists", name));
+        }
+        let name = identifier_name(&name);
+        let columns = Self::stored_column_names(columns)?;
+
+        // Constraints may only refer to columns of the table being created
+        let draft = Table {
+            name: name.clone(),
+            columns: columns.clone(),
+            constraints: Vec::new(),
+            rows: Vec::new(),
+        };
+        let mut constraints = constraints;
+        for constraint in constraints.iter_mut() {
+            match constraint {
+                TableConstraint::Check { condition, .. } => {
+                    Self::resolve_where(&draft, condition)?;
+                }
+                TableConstraint::ForeignKey(foreign_key) => {
+                    self.resolve_foreign_key(&draft, foreign_key)?;
+                }
+            }
+        }
+
+        // Each auto-increment column draws its values from a sequence of its own
+        let mut columns = columns;
+        let mut sequences = Vec::new();
+        for column in columns.iter_mut().filter(|c| c.auto_increment) {
+            sequences.push(self.serial_sequence(&name, column)?);
+        }
+        if !sequences.is_empty() {
+            for sequence in sequences {
+                self.catalog.create_sequence(sequence)?;
+            }
+            self.save_sequences()?;
+        }
+
+        self.catalog.create_table(name.clone(), columns, constraints)?;
+        
+        // Get the table we just created and save it to disk
+        let table = self.catalog.find_table(&quote_identifier(&name))
+            .ok_or_else(|| format!("Failed to find table '{}' after creation", name))?
+            .clone();
+        
+        self.database.save_table(&table, true)?;
+        Ok(())
+    }
+
+    /// Creates a table holding the result of a SELECT and returns the number of rows copied.
+    /// Column names and types come from the selected columns; constraints and defaults are not
+    /// carried over. A JSON path keeps JSON for `->` and gives TEXT for `->>`, and is named after
+    /// its last key. Other expressions are named as written unless aliased.
+    pub fn execute_create_table_as(
+        &mut self,
+        name: String,
+        source: String,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereClause>,
+        order_by: Vec<SortKey>,
+    ) -> Result<usize, String> {
+        if self.catalog.find_table(&name).is_some() {
+            return Err(format!("Table '{}' already exists", name));
+        }
+        let name = identifier_name(&name);
+
+        let source_table = self
+            .catalog
+            .find_table(&source)
+            .ok_or_else(|| format!("Table '{}' does not exist", source))?;
+        let output = Self::output_columns(source_table, &columns)?;
+        let rows = Self::select_rows(self.sort_memory, source_table, &output, where_clause.as_ref(), &order_by)?;
+
+        let mut new_columns = Vec::with_capacity(output.len());
+        for (position, output_column) in output.iter().enumerate() {
+            let mut column = match &output_column.expr {
+                reference @ (Expr::Identifier(_) | Expr::JsonPath { .. }) => {
+                    let operand = ColumnOperand::resolve(source_table, reference)?;
+                    let source_column = &source_table.columns[operand.index];
+                    match &operand.json_path {
+                        None => Column {
+                            name: source_column.name.clone(),
+                            data_type: source_column.data_type.clone(),
+                            type_params: source_column.type_params.clone(),
+                            ..Default::default()
+                        },
+                        Some(path) => Column {
+                            name: path.steps.iter().rev()
+                                .find_map(|step| match step {
+                                    JsonPathStep::Key(key) => Some(key.clone()),
+                                    JsonPathStep::Index(_) => None,
+                                })
+                                .unwrap_or_else(|| source_column.name.clone()),
+                            data_type: if path.as_text { "TEXT" } else { "JSON" }.to_string(),
+                            ..Default::default()
+                        },
+                    }
+                }
+                // Other expressions take the type of their first non-NULL result
+                _ => Column {
+                    name: output_column.header.clone(),
+                    data_type: rows.iter()
+                        .map(|row| &row[position])
+                        .find(|value| !value.value.is_empty())
+                        .and_then(|value| value.data_type.clone())
+                        .unwrap_or_else(|| "TEXT".to_string()),
+                    ..Default::default()
+                },
+            };
+            if output_column.aliased {
+                column.name = output_column.header.clone();
+            }
+            new_columns.push(column);
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = new_columns.iter().find(|c| !seen.insert(c.name.clone())) {
+            return Err(format!("Column '{}' specified more than once", duplicate.name));
+        }
+
+        let table = Table {
+            name,
+            columns: new_columns,
+            constraints: Vec::new(),
+            rows: rows.into_iter()
+                .map(|row| Row { values: row.into_iter().map(|v| v.value).collect() })
+                .collect(),
+        };
+
+        self.database.save_table(&table, true)?;
+        let row_count = table.rows.len();
+        self.catalog.add_table(table);
+        Ok(row_count)
+    }
+
+    pub fn execute_insert(&mut self, table: String, columns: Vec<String>, values: Vec<Expr>) -> Result<(), String> {
+        let table_ref = self
+            .catalog
+            .find_table(&table)
+            .ok_or_else(|| format!("Table '{}' does not exist", table))?;
+
+        // Resolve the target columns; without a column list every column is a target
+        let targets: Vec<usize> = if columns.is_empty() {
+            (0..table_ref.columns.len()).collect()
+        } else {
+            let mut targets = Vec::new();
+            for col_name in &columns {
+                match table_ref.column_index(col_name) {
+                    Some(index) if targets.contains(&index) => {
+                        return Err(format!("Column '{}' specified more than once", col_name));
+                    }
+                    Some(index) => targets.push(index),
+                    None => return Err(format!("Column '{}' not found in table '{}'", col_name, table_ref.name)),
+                }
+            }
+            targets
+        };
+
+        // Validate column count
+        if values.len() != targets.len() {
+            return Err(format!(
+                "Column count mismatch: expected {}, got {}",
+                targets.len(),
+                values.len()
+            ));
+        }
+
+        // Columns that were not given a value, or were given DEFAULT, take their default
+        let mut given: Vec<Option<&Expr>> = vec![None; table_ref.columns.len()];
+        for (&index, value) in targets.iter().zip(&values) {
+            given[index] = Some(value);
+        }
+        let table_columns = table_ref.columns.clone();
+        let mut row_values = Vec::with_capacity(table_columns.len());
+        for (column, value) in table_columns.iter().zip(given) {
+            let value = match value {
+                Some(v) if *v != Expr::Default => {
+                    let value = self.evaluate_value_mut(v)?;
+                    if column.auto_increment {
+                        self.advance_sequence(column, &value)?;
+                    }
+                    value
+                }
+                _ => self.default_value(column)?,
+            };
+            row_values.push(Self::coerce_value(&value, column)?);
+        }
+        let values = row_values;
+
+        let table_ref = self
+            .catalog
+            .find_table(&table)
+            .ok_or_else(|| format!("Table '{}' does not exist", table))?;
+        let added = [Row { values }];
+        Self::check_row_constraints(table_ref, &added)?;
+        let name = table_ref.name.clone();
+        let mut rows = table_ref.rows.clone();
+        rows.extend(added.iter().cloned());
+        let mut keys = self.check_unique_keys(&name, &[], &added)?;
+        if let Err(e) = self.check_foreign_keys(self.catalog.find_table(&table).ok_or("Table disappeared during INSERT")?, &rows) {
+            self.catalog.store_unique_keys(&name, keys);
+            return Err(e);
+        }
+
+        let table_ref = self
+            .catalog
+            .find_table_mut(&table)
+            .ok_or_else(|| format!("Table '{}' does not exist", table))?;
+        table_ref.rows = rows;
+        
+        // Save updated table to disk
+        let table_clone = table_ref.clone();
+        keys.apply(&table_clone, &[], &added);
+        self.catalog.store_unique_keys(&name, keys);
+        self.database.update_table_data(&table_clone)?;
+        Ok(())
+    }
+
+    pub fn execute_select(
+        &self,
+        table_name: String,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereClause>,
+        order_by: Vec<SortKey>,
+    ) -> Result<(Vec<String>, Vec<Row>), String> {
+        let table = self
+            .catalog
+            .find_table(&table_name)
+            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+
+        let output = Self::output_columns(table, &columns)?;
+        let rows = Self::select_rows(self.sort_memory, table, &output, where_clause.as_ref(), &order_by)?
+            .into_iter()
+            .map(|row| Row { values: row.into_iter().map(|v| v.value).collect() })
+            .collect();
+
+        Ok((output.into_iter().map(|c| c.header).collect(), rows))
+    }
+
+    /// Expands a SELECT list against `table`: `*` and `table.*` stand for all of its columns.
+    /// Every column an expression refers to must exist, even if no row is selected.
+    fn output_columns(table: &Table, items: &[SelectItem]) -> Result<Vec<OutputColumn>, String> {
+        let mut output = Vec::new();
+        for item in items {
+            match item {
+                SelectItem::Wildcard(qualifier) => {
+                    if let Some(qualifier) = qualifier.as_ref().filter(|q| !identifier_matches(&table.name, q)) {
+                        return Err(format!("Table '{}' is not in the FROM clause", identifier_name(qualifier)));
+                    }
+                    output.extend(table.columns.iter().map(|c| OutputColumn {
+                        expr: Expr::Identifier(quote_identifier(&c.name)),
+                        header: c.name.clone(),
+                        aliased: false,
+                    }));
+                }
+                SelectItem::Expr { expr, text, alias } => {
+                    for reference in expr.column_refs() {
+                        ColumnOperand::resolve(table, reference)?;
+                    }
+                    output.push(OutputColumn {
+                        expr: expr.clone(),
+                        header: alias.as_deref().map_or_else(|| text.clone(), identifier_name),
+                        aliased: alias.is_some(),
+                    });
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Evaluates `output` for every row of `table` matching `where_clause`, in the order given
+    /// by `order_by`. Sorting uses up to `sort_memory` bytes before spilling to a temporary file.
+    fn select_rows(
+        sort_memory: usize,
+        table: &Table,
+        output: &[OutputColumn],
+        where_clause: Option<&WhereClause>,
+        order_by: &[SortKey],
+    ) -> Result<Vec<Vec<TypedValue>>, String> {
+        let rows = Self::matching_rows(table, where_clause)?;
+        let evaluate = |row: usize, exprs: &[Expr]| {
+            exprs.iter().map(|e| Self::evaluate_expr(table, &table.rows[row], e)).collect::<Result<Vec<_>, _>>()
+        };
+        let mut exprs: Vec<Expr> = output.iter().map(|c| c.expr.clone()).collect();
+        if order_by.is_empty() {
+            return rows.into_iter().map(|i| evaluate(i, &exprs)).collect();
+        }
+
+        // Each record holds the sort keys followed by the output columns, both as value and type
+        let keys = order_by.iter()
+            .map(|key| Self::resolve_sort_key(table, output, &key.expr))
+            .collect::<Result<Vec<_>, _>>()?;
+        let key_count = keys.len();
+        exprs.splice(0..0, keys);
+        let records = rows.into_iter().map(|i| {
+            let values = evaluate(i, &exprs)?;
+            Ok(values.into_iter().flat_map(|v| [v.value, v.data_type.unwrap_or_default()]).collect())
+        });
+        let compare = |a: &[String], b: &[String]| {
+            order_by.iter().enumerate()
+                .map(|(i, key)| compare_sort_values(&typed_field(a, i), &typed_field(b, i), key))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        };
+
+        // Only the output columns of each record are kept as the merge hands them out
+        external_sort(records, sort_memory, compare)?
+            .map(|record| record.map(|record| (key_count..record.len() / 2).map(|i| typed_field(&record, i)).collect()))
+            .collect()
+    }
+
+    /// Returns the expression an ORDER BY key sorts by. A position such as `2` or the alias of
+    /// an output column refers to that column; anything else is evaluated against the table.
+    fn resolve_sort_key(table: &Table, output: &[OutputColumn], key: &Expr) -> Result<Expr, String> {
+        if let Expr::Literal(Literal::Number(position)) = key {
+            if let Ok(position) = position.parse::<usize>() {
+                return match position.checked_sub(1).and_then(|i| output.get(i)) {
+                    Some(column) => Ok(column.expr.clone()),
+                    None => Err(format!("ORDER BY position {} is not in the select list", position)),
+                };
+            }
+        }
+        if let Expr::Identifier(name) = key {
+            if let Some(column) = output.iter().find(|c| c.aliased && identifier_matches(&c.header, name)) {
+                return Ok(column.expr.clone());
+            }
+        }
+
+        for reference in key.column_refs() {
+            ColumnOperand::resolve(table, reference)?;
+        }
+        Ok(key.clone())
+    }
+
+    pub fn execute_update(&mut self, table_name: String, set_clause: (String, Expr), where_clause: Option<WhereClause>) -> Result<usize, String> {
+        let table = self
+            .catalog
+            .find_table(&table_name)
+            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+
+        let (column_to_set, new_value) = set_clause;
+
+        let column_to_set_index = table.column_index(&column_to_set);
+
+        let set_col_idx = match column_to_set_index {
+            Some(index) => index,
+            None => return Err(format!("Column '{}' not found in table '{}'", column_to_set, table.name)),
+        };
+
+        // If there's a WHERE clause, filter by it. Otherwise, update all rows.
+        let matching_rows = Self::matching_rows(table, where_clause.as_ref())?;
+        for reference in new_value.column_refs() {
+            ColumnOperand::resolve(table, reference)?;
+        }
+        let mut updated_rows = table.rows.clone();
+        let schema = Table {
+            name: table.name.clone(),
+            columns: table.columns.clone(),
+            constraints: Vec::new(),
+            rows: Vec::new(),
+        };
+
+        // Compute every new value before touching the table, so a rejected value leaves it unchanged.
+        // Values are evaluated against the row as it was, and functions per row, giving each row
+        // its own gen_random_uuid().
+        let column = &schema.columns[set_col_idx];
+        let mut new_values = Vec::with_capacity(matching_rows.len());
+        for &row_index in &matching_rows {
+            let value = if new_value == Expr::Default {
+                self.default_value(column)?
+            } else {
+                self.evaluate_row_value_mut(&schema, &updated_rows[row_index], &new_value)?
+            };
+            new_values.push(Self::coerce_value(&value, column)?);
+        }
+
+        let table = self
+            .catalog
+            .find_table(&table_name)
+            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+        let mut updated_count = 0;
+        let mut removed = Vec::with_capacity(matching_rows.len());
+        let mut added = Vec::with_capacity(matching_rows.len());
+        for (row_index, value) in matching_rows.into_iter().zip(new_values) {
+            removed.push(updated_rows[row_index].clone());
+            if let Some(val_to_update) = updated_rows[row_index].values.get_mut(set_col_idx) {
+                *val_to_update = value;
+                updated_count += 1;
+            }
+            added.push(updated_rows[row_index].clone());
+        }
+
+        // Only the changed rows need checking, the others already satisfied the constraints
+        Self::check_row_constraints(table, &added)?;
+        let name = table.name.clone();
+        let mut keys = self.check_unique_keys(&name, &removed, &added)?;
+        let table = self
+            .catalog
+            .find_table(&table_name)
+            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+        if let Err(e) = self.check_foreign_keys(table, &updated_rows)
+            .and_then(|_| self.check_references_to(table, &updated_rows))
+        {
+            self.catalog.store_unique_keys(&name, keys);
+            return Err(e);
+        }
+
         let table = self
             .catalog
             .find_table_mut(&table_name)
             .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+        table.rows = updated_rows;
 
-        table.rows.clear();
-        
         let table_clone = table.clone();
+        keys.apply(&table_clone, &removed, &added);
+        self.catalog.store_unique_keys(&name, keys);
         self.database.update_table_data(&table_clone)?;
 
+        Ok(updated_count)
+    }
+
+    /// Deletes the rows of a table matching the WHERE clause, or all rows without one,
+    /// applying the ON DELETE actions of referencing tables. Returns the number of rows deleted
+    /// from the table itself.
+    pub fn execute_delete(&mut self, table_name: String, where_clause: Option<WhereClause>) -> Result<usize, String> {
+        let table = self
+            .catalog
+            .find_table(&table_name)
+            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+
+        let matching_rows = Self::matching_rows(table, where_clause.as_ref())?;
+        if matching_rows.is_empty() {
+            return Ok(0);
+        }
+
+        let deleted_count = matching_rows.len();
+        let mut pending = HashMap::new();
+        self.delete_rows(&mut pending, table, matching_rows)?;
+        self.commit_pending(pending)?;
+
+        Ok(deleted_count)
+    }
+
+    pub fn execute_truncate_table(&mut self, table_name: String) -> Result<(), String> {
+        let table = self
+            .catalog
+            .find_table(&table_name)
+            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?;
+
+        // Truncating removes every row, so referencing tables get their ON DELETE actions
+        let mut pending = HashMap::new();
+        self.delete_rows(&mut pending, table, (0..table.rows.len()).collect())?;
+        self.commit_pending(pending)
+    }
+
+    /// Changes the definition of a table, rewriting its rows to match.
+    pub fn execute_alter_table(&mut self, table_name: String, action: AlterAction) -> Result<(), String> {
+        let old = self
+            .catalog
+            .find_table(&table_name)
+            .ok_or_else(|| format!("Table '{}' does not exist", table_name))?
+            .clone();
+        let mut table = old.clone();
+
+        let children = match action {
+            AlterAction::AddColumn { column, constraints } => {
+                self.add_column(&mut table, column, constraints)?;
+                Vec::new()
+            }
+            AlterAction::DropColumn { name } => {
+                let dropped = self.drop_column(&mut table, &name)?;
+                // The sequence of a dropped auto-increment column goes with it
+                if let Some(sequence) = dropped.default.as_ref().and_then(nextval_argument).filter(|_| dropped.auto_increment) {
+                    let sequence = self.sequence_reference(sequence);
+                    self.catalog.remove_sequence(&sequence);
+                    self.save_sequences()?;
+                }
+                Vec::new()
+            }
+            AlterAction::RenameColumn { from, to } => self.rename_column(&mut table, &from, &to)?,
+            AlterAction::RenameTable { to } => self.rename_table(&mut table, &to)?,
+        };
+
+        self.database.replace_table(&old, &table)?;
+        *self.catalog.find_table_mut(&quote_identifier(&old.name)).ok_or("Table disappeared during ALTER TABLE")? = table;
+
+        // Tables whose foreign keys name a renamed column are rewritten with the new name
+        for child in children {
+            let old_child = self.catalog.find_table(&quote_identifier(&child.name)).ok_or("Table disappeared during ALTER TABLE")?.clone();
+            self.database.replace_table(&old_child, &child)?;
+            *self.catalog.find_table_mut(&quote_identifier(&old_child.name)).ok_or("Table disappeared during ALTER TABLE")? = child;
+        }
+
         Ok(())
     }
 
+    /// Adds `column` to `table` and fills it in for the existing rows from its default.
+    fn add_column(&mut self, table: &mut Table, mut column: Column, mut constraints: Vec<TableConstraint>) -> Result<(), String> {
+        if table.column_index(&column.name).is_some() {
+            return Err(format!("Column '{}' already exists in table '{}'", column.name, table.name));
+        }
+        column.name = identifier_name(&column.name);
+        if column.primary_key && !table.primary_key_indices().is_empty() {
+            return Err(format!("Table '{}' already has a primary key", table.name));
+        }
+        let sequence = if column.auto_increment {
+            Some(self.serial_sequence(&table.name, &mut column)?)
+        } else {
+            None
+        };
+
+        table.columns.push(column.clone());
+        for constraint in constraints.iter_mut() {
+            match constraint {
+                TableConstraint::Check { condition, .. } => {
+                    Self::resolve_where(table, condition)?;
+                }
+                TableConstraint::ForeignKey(foreign_key) => {
+                    self.resolve_foreign_key(table, foreign_key)?;
+                }
+            }
+        }
+        table.constraints.extend(constraints);
+
+        if let Some(sequence) = &sequence {
+            self.catalog.create_sequence(sequence.clone())?;
+        }
+        let result = self.backfill_column(table, &column);
+        if result.is_err() {
+            if let Some(sequence) = &sequence {
+                self.catalog.remove_sequence(&quote_identifier(&sequence.name));
+            }
+        }
+        if sequence.is_some() {
+            self.save_sequences()?;
+        }
+        result
+    }
+
+    /// Appends the default of a newly added `column` to every row of `table` and validates the result.
+    fn backfill_column(&mut self, table: &mut Table, column: &Column) -> Result<(), String> {
+        let mut values = Vec::with_capacity(table.rows.len());
+        for _ in &table.rows {
+            let value = self.default_value(column)?;
+            values.push(Self::coerce_value(&value, column)?);
+        }
+        for (row, value) in table.rows.iter_mut().zip(values) {
+            row.values.push(value);
+        }
+
+        Self::check_constraints(table, &table.rows)?;
+        self.check_foreign_keys(table, &table.rows)
+    }
+
+    /// Removes the column `name` and its values from `table`, returning the removed column.
+    /// Columns used by a CHECK constraint or a foreign key cannot be dropped.
+    fn drop_column(&self, table: &mut Table, name: &str) -> Result<Column, String> {
+        let index = table.column_indices(&[name.to_string()])?[0];
+        let column_name = table.columns[index].name.clone();
+        if table.columns.len() == 1 {
+            return Err(format!("Cannot drop '{}', the only column of table '{}'", column_name, table.name));
+        }
+
+        for constraint in &table.constraints {
+            let uses_column = match constraint {
+                TableConstraint::Check { condition, .. } => condition
+                    .predicates()
+                    .iter()
+                    .flat_map(|p| p.operands())
+                    .flat_map(Expr::column_refs)
+                    .any(|reference| ColumnOperand::resolve(table, reference).is_ok_and(|c| c.index == index)),
+                TableConstraint::ForeignKey(foreign_key) => foreign_key.columns.contains(&column_name),
+            };
+            if uses_column {
+                return Err(format!("Cannot drop column '{}': it is used by {}", column_name, describe_constraint(constraint)));
+            }
+        }
+        for child in self.catalog.get_all_tables() {
+            for foreign_key in child.foreign_keys() {
+                if foreign_key.parent_table == table.name
+                    && foreign_key.parent_columns.contains(&column_name)
+                {
+                    return Err(format!(
+                        "Cannot drop column '{}': it is referenced by foreign key {} of '{}'",
+                        column_name, foreign_key, child.name
+                    ));
+                }
+            }
+        }
+
+        // Dropping part of the primary key drops the key as a whole
+        let column = table.columns.remove(index);
+        if column.primary_key {
+            table.columns.iter_mut().for_each(|c| c.primary_key = false);
+        }
+        for row in table.rows.iter_mut() {
+            row.values.remove(index);
+        }
+
+        Ok(column)
+    }
+
+    /// Renames a column of `table`, updating the constraints that name it. Returns the other
+    /// tables whose foreign keys reference the column, with the new name filled in.
+    fn rename_column(&self, table: &mut Table, from: &str, to: &str) -> Result<Vec<Table>, String> {
+        let index = table.column_indices(&[from.to_string()])?[0];
+        // A column may be renamed to a different spelling of its own name
+        if table.column_index(to).is_some_and(|i| i != index) {
+            return Err(format!("Column '{}' already exists in table '{}'", to, table.name));
+        }
+        let from = table.columns[index].name.clone();
+        let to_reference = to;
+        let to = &identifier_name(to);
+        table.columns[index].name = to.clone();
+
+        let table_name = table.name.clone();
+        for constraint in table.constraints.iter_mut() {
+            match constraint {
+                TableConstraint::Check { expression, condition } => {
+                    let parser = Parser::new();
+                    let renamed = parser.rename_column_in_check(expression, &from, to_reference);
+                    if let Some(TableConstraint::Check { expression: e, condition: c }) =
+                        renamed.and_then(|r| parser.parse_check(&r))
+                    {
+                        *expression = e;
+                        *condition = c;
+                    }
+                }
+                TableConstraint::ForeignKey(foreign_key) => {
+                    rename_in_list(&mut foreign_key.columns, &from, to);
+                    // A self-referencing key names the column on the parent side as well
+                    if foreign_key.parent_table == table_name {
+                        rename_in_list(&mut foreign_key.parent_columns, &from, to);
+                    }
+                }
+            }
+        }
+
+        let mut children = Vec::new();
+        for child in self.catalog.get_all_tables().iter().filter(|t| t.name != table_name) {
+            let mut child = child.clone();
+            let mut changed = false;
+            for constraint in child.constraints.iter_mut() {
+                if let TableConstraint::ForeignKey(foreign_key) = constraint {
+                    if foreign_key.parent_table == table_name {
+                        changed |= rename_in_list(&mut foreign_key.parent_columns, &from, to);
+                    }
+                }
+            }
+            if changed {
+                children.push(child);
+            }
+        }
+
+        Ok(children)
+    }
+
+    /// Renames `table` to `to`, along with the sequences of its auto-increment columns. Returns the
+    /// other tables whose foreign keys reference it, with the new name filled in.
+    fn rename_table(&mut self, table: &mut Table, to: &str) -> Result<Vec<Table>, String> {
+        // A table may be renamed to a different spelling of its own name
+        if let Some(existing) = self.catalog.find_table(to).filter(|t| t.name != table.name) {
+            return Err(format!("Table '{}' already exists", existing.name));
+        }
+        let from = table.name.clone();
+        let to = &identifier_name(to);
+        table.name = to.clone();
+
+        for foreign_key in table.constraints.iter_mut().filter_map(|c| match c {
+            TableConstraint::ForeignKey(foreign_key) if foreign_key.parent_table == from => Some(foreign_key),
+            _ => None,
+        }) {
+            foreign_key.parent_table = to.to_string();
+        }
+
+        // Sequences named after the table follow it, so the old name can be reused freely
+        let mut sequences_renamed = false;
+        for column in table.columns.iter_mut().filter(|c| c.auto_increment) {
+            let old_sequence = format!("{}_{}_seq", from, column.name);
+            let new_sequence = format!("{}_{}_seq", to, column.name);
+            if column.default.as_ref().and_then(nextval_argument) != Some(old_sequence.as_str())
+                || self.catalog.find_sequence(&quote_identifier(&new_sequence)).is_some()
+            {
+                continue;
+            }
+            if let Some(sequence) = self.catalog.find_sequence_mut(&quote_identifier(&old_sequence)) {
+                sequence.name = new_sequence.clone();
+                column.default = Some(nextval_call(&new_sequence));
+                sequences_renamed = true;
+            }
+        }
+        if sequences_renamed {
+            self.save_sequences()?;
+        }
+
+        let mut children = Vec::new();
+        for child in self.catalog.get_all_tables().iter().filter(|t| t.name != from) {
+            let mut child = child.clone();
+            let mut changed = false;
+            for constraint in child.constraints.iter_mut() {
+                if let TableConstraint::ForeignKey(foreign_key) = constraint {
+                    if foreign_key.parent_table == from {
+                        foreign_key.parent_table = to.to_string();
+                        changed = true;
+                    }
+                }
+            }
+            if changed {
+                children.push(child);
+            }
+        }
+
+        Ok(children)
+    }
+
+    /// Drops a table along with the sequences of its auto-increment columns. Returns false when
+    /// the table does not exist and `if_exists` is set.
+    pub fn execute_drop_table(&mut self, table_name: String, if_exists: bool) -> Result<bool, String> {
+        let Some(table) = self.catalog.find_table(&table_name) else {
+            if if_exists {
+                return Ok(false);
+            }
+            return Err(format!("Table '{}' does not exist", table_name));
+        };
+
+        for child in self.catalog.get_all_tables().iter().filter(|t| t.name != table.name) {
+            if let Some(foreign_key) = child.foreign_keys().find(|fk| fk.parent_table == table.name) {
+                return Err(format!(
+                    "Cannot drop table '{}': it is referenced by foreign key {} of '{}'",
+                    table.name, foreign_key, child.name
+                ));
+            }
+        }
+
+        let table = table.clone();
+        self.database.drop_table(&table)?;
+        self.catalog.remove_table(&quote_identifier(&table.name));
+
+        let owned_sequences: Vec<String> = table.columns.iter()
+            .filter(|c| c.auto_increment)
+            .filter_map(|c| c.default.as_ref().and_then(nextval_argument))
+            .map(|sequence| self.sequence_reference(sequence))
+            .collect();
+        if !owned_sequences.is_empty() {
+            for sequence in owned_sequences {
+                self.catalog.remove_sequence(&sequence);
+            }
+            self.save_sequences()?;
+        }
+
+        Ok(true)
+    }
+
+    pub fn execute_create_sequence(&mut self, name: String, start: i64, increment: i64) -> Result<(), String> {
+        let last_value = start.checked_sub(increment)
+            .ok_or_else(|| format!("START {} is out of range for sequence '{}'", start, name))?;
+        if self.catalog.find_sequence(&name).is_some() {
+            return Err(format!("Sequence '{}' already exists", name));
+        }
+        let name = identifier_name(&name);
+        self.catalog.create_sequence(Sequence { name, increment, last_value })?;
+        self.save_sequences()
+    }
+
+    /// Drops a sequence that no column default draws from. Returns false when the sequence
+    /// does not exist and `if_exists` is set.
+    pub fn execute_drop_sequence(&mut self, name: String, if_exists: bool) -> Result<bool, String> {
+        let Some(sequence) = self.catalog.find_sequence(&name) else {
+            if if_exists {
+                return Ok(false);
+            }
+            return Err(format!("Sequence '{}' does not exist", name));
+        };
+
+        for table in self.catalog.get_all_tables() {
+            let user = table.columns.iter().find(|c| {
+                c.default.as_ref().and_then(nextval_argument)
+                    .and_then(|s| self.catalog.find_sequence(&self.sequence_reference(s)))
+                    .is_some_and(|used| used.name == sequence.name)
+            });
+            if let Some(column) = user {
+                return Err(format!(
+                    "Cannot drop sequence '{}': it is used by column '{}' of '{}'",
+                    sequence.name, column.name, table.name
+                ));
+            }
+        }
+
+        let name = quote_identifier(&sequence.name);
+        self.catalog.remove_sequence(&name);
+        self.save_sequences()?;
+        Ok(true)
+    }
+
+    pub fn get_sequence(&self, name: &str) -> Option<&Sequence> {
+        self.catalog.find_sequence(name)
+    }
+
     pub fn get_table_schema(&self, table: &str) -> Option<&Table> {
         self.catalog.find_table(table)
     }
@@ -6,17 +6,21 @@ pub mod parser;
 pub mod engine;
 pub mod database;
 pub mod wal;
+pub mod sort;
 
-use parser::{Command, Parser};
+use parser::{identifier_name, Command, ParseError, Parser, TableConstraint};
 use engine::QueryEngine;
 
-/// Executes a single line of input against the query engine.
+/// Executes a line of input against the query engine.
 ///
 /// This function encapsulates the command processing logic, taking a line of text,
 /// parsing it, executing the corresponding command, and returning the result
 /// as a formatted string. This allows both the CLI and the server to share the
 /// same command execution logic without duplicating code or printing directly to stdout.
 ///
+/// The input may be a script of several statements separated by semicolons; their results
+/// are joined by blank lines. See `execute_script`.
+///
 /// # Arguments
 /// * `input` - A string slice representing the command to be executed.
 /// * `query_engine` - A mutable reference to the `QueryEngine` instance.
@@ -33,35 +37,90 @@ pub fn execute_line(input: &str, query_engine: &mut QueryEngine, parser: &Parser
 
     // Handle special commands that don't require SQL parsing.
     // This logic is kept separate from the SQL command parsing.
-    match input.to_lowercase().as_str() {
-        "help" => {
-            return print_help();
-        }
-        // Note: "exit" and "quit" are not handled here because they are process-specific.
-        // The caller (CLI or server) is responsible for managing its own lifecycle.
-        _ => {}
+    // Note: "exit" and "quit" are not handled here because they are process-specific.
+    // The caller (CLI or server) is responsible for managing its own lifecycle.
+    if input.eq_ignore_ascii_case("help") {
+        return print_help();
     }
 
-    // Parse and execute the SQL command using the provided parser.
-    let command = parser.parse(input);
+    execute_script(input, query_engine, parser).join("\n\n")
+}
+
+/// Executes a script of statements separated by semicolons, in order, and returns the result
+/// of each statement. `--` and `/* */` comments are ignored. A statement that fails to parse
+/// or execute gives an error result and does not stop the statements after it.
+pub fn execute_script(script: &str, query_engine: &mut QueryEngine, parser: &Parser) -> Vec<String> {
+    parser
+        .parse_script(script)
+        .into_iter()
+        .map(|result| match result {
+            Ok(command) => execute_command(command, query_engine),
+            Err(e) => format_parse_error(script, &e),
+        })
+        .collect()
+}
+
+/// Executes a parsed command and formats its result.
+fn execute_command(command: Command, query_engine: &mut QueryEngine) -> String {
     match command {
-        Command::CreateTable { name, columns } => {
-            match query_engine.execute_create_table(name.clone(), columns) {
-                Ok(_) => format!("Table '{}' created successfully", name),
+        Command::CreateTable { name, columns, constraints, if_not_exists } => {
+            if if_not_exists && query_engine.get_table_schema(&name).is_some() {
+                return format!("Notice: table '{}' already exists, skipping", identifier_name(&name));
+            }
+            match query_engine.execute_create_table(name.clone(), columns, constraints) {
+                Ok(_) => format!("Table '{}' created successfully", identifier_name(&name)),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::CreateTableAs { name, if_not_exists, source, columns, where_clause, order_by } => {
+            if if_not_exists && query_engine.get_table_schema(&name).is_some() {
+                return format!("Notice: table '{}' already exists, skipping", identifier_name(&name));
+            }
+            match query_engine.execute_create_table_as(name.clone(), source, columns, where_clause, order_by) {
+                Ok(count) => format!("Table '{}' created with {} rows", identifier_name(&name), count),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::DropTable { name, if_exists } => {
+            match query_engine.execute_drop_table(name.clone(), if_exists) {
+                Ok(true) => format!("Table '{}' dropped successfully", identifier_name(&name)),
+                Ok(false) => format!("Notice: table '{}' does not exist, skipping", identifier_name(&name)),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::AlterTable { table, action } => {
+            match query_engine.execute_alter_table(table.clone(), action) {
+                Ok(_) => format!("Table '{}' altered successfully", identifier_name(&table)),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::CreateSequence { name, start, increment, if_not_exists } => {
+            if if_not_exists && query_engine.get_sequence(&name).is_some() {
+                return format!("Notice: sequence '{}' already exists, skipping", identifier_name(&name));
+            }
+            match query_engine.execute_create_sequence(name.clone(), start, increment) {
+                Ok(_) => format!("Sequence '{}' created successfully", identifier_name(&name)),
                 Err(e) => format!("Error: {}", e),
             }
         }
-        Command::Insert { table, values } => {
-            match query_engine.execute_insert(table.clone(), values) {
-                Ok(_) => format!("Inserted 1 row into '{}'", table),
+        Command::DropSequence { name, if_exists } => {
+            match query_engine.execute_drop_sequence(name.clone(), if_exists) {
+                Ok(true) => format!("Sequence '{}' dropped successfully", identifier_name(&name)),
+                Ok(false) => format!("Notice: sequence '{}' does not exist, skipping", identifier_name(&name)),
                 Err(e) => format!("Error: {}", e),
             }
         }
-        Command::Select { table, columns, where_clause } => {
-            match query_engine.execute_select(table.clone(), columns, where_clause) {
+        Command::Insert { table, columns, values } => {
+            match query_engine.execute_insert(table.clone(), columns, values) {
+                Ok(_) => format!("Inserted 1 row into '{}'", identifier_name(&table)),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::Select { table, columns, where_clause, order_by } => {
+            match query_engine.execute_select(table.clone(), columns, where_clause, order_by) {
                 Ok((cols, rows)) => {
                     if rows.is_empty() {
-                        format!("No rows found in '{}'", table)
+                        format!("No rows found in '{}'", identifier_name(&table))
                     } else {
                         // Format the output as a text-based table.
                         let mut output = String::new();
@@ -96,28 +155,68 @@ pub fn execute_line(input: &str, query_engine: &mut QueryEngine, parser: &Parser
         }
         Command::InspectTable { name } => {
             if let Some(table) = query_engine.get_table_schema(&name) {
-                let mut output = format!("Table: {}\n", name);
+                let mut output = format!("Table: {}\n", table.name);
                 output.push_str("----------------\n");
-                output.push_str(&format!("{:<20} | {}\n", "Column", "Type"));
-                output.push_str(&format!("{:-<20}-+-{:-<15}\n", "", ""));
+                output.push_str(&format!("{:<20} | {:<15} | {}\n", "Column", "Type", "Constraints"));
+                output.push_str(&format!("{:-<20}-+-{:-<15}-+-{:-<15}\n", "", "", ""));
                 
                 for column in &table.columns {
-                    output.push_str(&format!("{:<20} | {}\n", column.name, column.data_type));
+                    let mut constraints = Vec::new();
+                    if column.primary_key {
+                        constraints.push("PRIMARY KEY".to_string());
+                    }
+                    if column.not_null {
+                        constraints.push("NOT NULL".to_string());
+                    }
+                    if column.unique {
+                        constraints.push("UNIQUE".to_string());
+                    }
+                    if column.auto_increment {
+                        constraints.push("AUTOINCREMENT".to_string());
+                    }
+                    if let Some(default) = &column.default {
+                        constraints.push(format!("DEFAULT {}", default));
+                    }
+                    let line = format!(
+                        "{:<20} | {:<15} | {}",
+                        column.name,
+                        column.type_name(),
+                        constraints.join(" ")
+                    );
+                    output.push_str(line.trim_end());
+                    output.push('\n');
+                }
+
+                for constraint in &table.constraints {
+                    match constraint {
+                        TableConstraint::Check { expression, .. } => {
+                            output.push_str(&format!("CHECK ({})\n", expression));
+                        }
+                        TableConstraint::ForeignKey(foreign_key) => {
+                            output.push_str(&format!("{}\n", foreign_key));
+                        }
+                    }
                 }
                 output.trim_end().to_string()
             } else {
-                format!("Table '{}' not found", name)
+                format!("Table '{}' not found", identifier_name(&name))
             }
         }
         Command::Update { table, set_column, set_value, where_clause } => {
             match query_engine.execute_update(table.clone(), (set_column, set_value), where_clause) {
-                Ok(count) => format!("Updated {} rows in '{}'", count, table),
+                Ok(count) => format!("Updated {} rows in '{}'", count, identifier_name(&table)),
+                Err(e) => format!("Error: {}", e),
+            }
+        }
+        Command::Delete { table, where_clause } => {
+            match query_engine.execute_delete(table.clone(), where_clause) {
+                Ok(count) => format!("Deleted {} rows from '{}'", count, identifier_name(&table)),
                 Err(e) => format!("Error: {}", e),
             }
         }
         Command::Truncate { table } => {
             match query_engine.execute_truncate_table(table.clone()) {
-                Ok(_) => format!("Table '{}' truncated successfully", table),
+                Ok(_) => format!("Table '{}' truncated successfully", identifier_name(&table)),
                 Err(e) => format!("Error: {}", e),
             }
         }
@@ -129,32 +228,58 @@ pub fn execute_line(input: &str, query_engine: &mut QueryEngine, parser: &Parser
                         Err(e) => format!("Error serializing to JSON: {}", e),
                     }
                 } else {
-                    format!("Table '{}' not found", table)
+                    format!("Table '{}' not found", identifier_name(&table))
                 }
             } else {
                 format!("Unsupported format: {}", format)
             }
         }
-        Command::Unknown(cmd) => {
-            format!("Unknown command: {}\nType 'help' for available commands", cmd)
-        }
     }
 }
 
+/// Formats a parse error followed by the offending line of `input`, with a caret under the
+/// position of the error.
+fn format_parse_error(input: &str, error: &ParseError) -> String {
+    let line = input.lines().nth(error.line - 1).unwrap_or("");
+    // Keep tabs so that the caret lines up with the line above it
+    let indent: String = line
+        .chars()
+        .take(error.column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    format!("Error: {}\n{}\n{}^", error, line, indent)
+}
+
 /// Returns a help string with available commands.
 ///
 /// This is a helper function to avoid cluttering the main execution logic.
 fn print_help() -> String {
     "Available commands:\n".to_owned() +
-    "  CREATE TABLE <table_name> (col1 TYPE, col2 TYPE, ...) - Create a new table\n" +
-    "  INSERT INTO <table_name> VALUES (val1, val2, ...) - Insert data into a table\n" +
+    "  CREATE TABLE [IF NOT EXISTS] <table_name> (col1 TYPE, col2 TYPE, ...) - Create a new table\n" +
+    "  ALTER TABLE <table_name> ADD [COLUMN] <column> TYPE [DEFAULT <value>] - Add a column\n" +
+    "  ALTER TABLE <table_name> DROP [COLUMN] <column> - Remove a column\n" +
+    "  ALTER TABLE <table_name> RENAME [COLUMN] <column> TO <new_name> - Rename a column\n" +
+    "  ALTER TABLE <table_name> RENAME TO <new_name> - Rename a table\n" +
+    "  CREATE TABLE <table_name> AS SELECT ... - Create a table from the result of a query\n" +
+    "  DROP TABLE [IF EXISTS] <table_name> - Remove a table and its data\n" +
+    "  CREATE SEQUENCE [IF NOT EXISTS] <name> [START WITH n] [INCREMENT BY n] - Create a sequence, read with nextval('<name>')\n" +
+    "  DROP SEQUENCE [IF EXISTS] <name> - Remove a sequence\n" +
+    "  INSERT INTO <table_name> [(col1, col2, ...)] VALUES (val1, val2, ...) - Insert data into a table\n" +
     "  SELECT * FROM <table_name> - Query data from a table\n" +
     "  SELECT * FROM <table_name> WHERE <column> [=, !=, <, >, <=, >=, LIKE, NOT LIKE] <value> - Query data with a where clause\n" +
+    "  SELECT <expression> [AS <alias>], <table_name>.*, ... FROM <table_name> - Select expressions such as price * qty or UPPER(name)\n" +
+    "  SELECT ... ORDER BY <expression> [ASC | DESC] [NULLS FIRST | NULLS LAST], ... - Sort the result by one or more keys\n" +
     "  UPDATE <table_name> SET <column> = <value> WHERE <column> [=, !=, <, >, <=, >=, LIKE, NOT LIKE] <value> - Update data in a table\n" +
-    "  TRUNCATE TABLE <table_name> - Remove all rows from a table\n" +
+    "  DELETE FROM <table_name> [WHERE <column> [=, !=, <, >, <=, >=, LIKE, NOT LIKE] <value>] - Delete rows from a table\n" +
+    "  TRUNCATE TABLE <table_name> - Remove all rows from a table, applying ON DELETE actions of referencing tables\n" +
     "  GET <table_name> AS JSON - Get a table's data in JSON format\n" +
     "  INSPECT <table_name> - Show table schema and column types\n" +
     "  SHOW TABLES - List all tables in the database\n" +
     "  help - Show this help message\n" +
-    "  exit | quit - Exit the program"
+    "  exit | quit - Exit the program\n" +
+    "Conditions can also be <column> [NOT] IN (v1, v2, ...) and <column> [NOT] BETWEEN <low> AND <high>.\n" +
+    "Conditions can be combined with AND, OR, NOT and parentheses, e.g. WHERE (a = 1 OR b > 2) AND NOT c LIKE 'x%'.\n" +
+    "Either side of a condition can be an expression over columns with + - * / %, e.g. WHERE price * qty > 100 OR updated_at > created_at.\n" +
+    "Statements separated by ';' run one after another, and may span several lines in the CLI.\n" +
+    "Text after -- up to the end of the line, and text between /* and */, is ignored."
 }
@@ -16,33 +16,58 @@ fn main() {
     let mut query_engine = QueryEngine::new();
     let parser = Parser::new();
 
+    // Statements may span several lines; they are collected here until they are complete.
+    let mut script = String::new();
+
     // The REPL loop is now much simpler.
     loop {
-        print!("isenta> ");
+        print!("{}", if script.is_empty() { "isenta> " } else { "   ...> " });
         io::stdout().flush().unwrap();
 
         let mut input = String::new();
-        if io::stdin().read_line(&mut input).is_err() {
-            println!("Error reading input");
-            continue;
+        match io::stdin().read_line(&mut input) {
+            Ok(0) => break, // End of input, e.g. a script piped into the CLI
+            Ok(_) => {}
+            Err(_) => {
+                println!("Error reading input");
+                continue;
+            }
         }
 
         let input = input.trim();
 
         // Handle process-specific commands like 'exit' or 'quit'.
         // The `execute_line` function does not handle these, as it's stateless.
-        match input.to_lowercase().as_str() {
-            "exit" | "quit" => {
-                println!("Goodbye!");
-                break;
-            }
-            _ => {
-                // All other commands are passed to the centralized execution function.
-                let result = execute_line(input, &mut query_engine, &parser);
-                if !result.is_empty() {
-                    println!("{}", result);
+        if script.is_empty() {
+            match input.to_lowercase().as_str() {
+                "exit" | "quit" => {
+                    println!("Goodbye!");
+                    break;
                 }
+                "" => continue,
+                _ => {}
             }
         }
+
+        // Keep reading until the statements end with a semicolon or parse as they are;
+        // an empty line runs whatever has been entered.
+        script.push_str(input);
+        script.push('\n');
+        let complete = input.is_empty() || script.trim().eq_ignore_ascii_case("help") || parser.is_complete(&script);
+        if !complete {
+            continue;
+        }
+
+        // All other commands are passed to the centralized execution function.
+        let result = execute_line(script.trim(), &mut query_engine, &parser);
+        if !result.is_empty() {
+            println!("{}", result);
+        }
+        script.clear();
+    }
+
+    // Run anything left unterminated at the end of the input
+    if !script.trim().is_empty() {
+        println!("{}", execute_line(script.trim(), &mut query_engine, &parser));
     }
-}
\ No newline at end of file
+}
@@ -1,10 +1,301 @@
+use pest::error::{ErrorVariant, InputLocation};
+use pest::iterators::Pair;
+use pest::Parser as _;
+use pest_derive::Parser;
 use serde::Serialize;
 
+/// The pest parser generated from `sql.pest`.
+#[derive(Parser)]
+#[grammar = "sql.pest"]
+struct SqlGrammar;
+
+/// A literal value written in a statement.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum Literal {
+    /// A number as written, e.g. `-42`.
+    Number(String),
+    /// The text of a single-quoted string, without the quotes and with `''` unescaped to `'`.
+    String(String),
+    Null,
+}
+
+/// An expression used as a value in a statement.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum Expr {
+    Literal(Literal),
+    /// An identifier as written, with the quotes of a quoted identifier. Where no column can be
+    /// referenced, as in `VALUES (1, alice)`, a bare word stands for its own text.
+    Identifier(String),
+    /// A column followed by JSON path operators, e.g. `doc->>'name'`; the column is kept as written.
+    JsonPath { column: String, path: JsonPath },
+    /// A function call such as `nextval('ids')`; the name is kept as written.
+    Function { name: String, args: Vec<Expr> },
+    Binary {
+        operator: ArithmeticOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    /// Unary minus, e.g. `-price`.
+    Negate(Box<Expr>),
+    /// The keyword DEFAULT, only allowed as an INSERT or UPDATE value.
+    Default,
+}
+
+impl Expr {
+    /// Returns the column references anywhere in the expression, identifiers and JSON paths,
+    /// from left to right.
+    pub fn column_refs(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Identifier(_) | Expr::JsonPath { .. } => vec![self],
+            Expr::Function { args, .. } => args.iter().flat_map(Expr::column_refs).collect(),
+            Expr::Binary { left, right, .. } => {
+                let mut references = left.column_refs();
+                references.extend(right.column_refs());
+                references
+            }
+            Expr::Negate(inner) => inner.column_refs(),
+            Expr::Literal(_) | Expr::Default => Vec::new(),
+        }
+    }
+
+    /// Writes `self` as an operand of `operator`, in parentheses where it would otherwise bind
+    /// differently. Operators are left-associative, so the right operand of an operator of the
+    /// same precedence needs them too.
+    fn fmt_operand(&self, f: &mut std::fmt::Formatter<'_>, operator: ArithmeticOp, right: bool) -> std::fmt::Result {
+        match self {
+            Expr::Binary { operator: inner, .. }
+                if inner.precedence() < operator.precedence() || (right && inner.precedence() == operator.precedence()) =>
+            {
+                write!(f, "({})", self)
+            }
+            _ => write!(f, "{}", self),
+        }
+    }
+}
+
+/// Writes an expression in a form `Parser::parse_expression` reads back, e.g. `price * (qty + 1)`.
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Literal(Literal::Number(number)) => write!(f, "{}", number),
+            Expr::Literal(Literal::String(text)) => write!(f, "'{}'", text.replace('\'', "''")),
+            Expr::Literal(Literal::Null) => write!(f, "NULL"),
+            Expr::Identifier(name) => write!(f, "{}", name),
+            Expr::JsonPath { column, path } => write!(f, "{}{}", column, path),
+            Expr::Function { name, args } => {
+                let args: Vec<String> = args.iter().map(Expr::to_string).collect();
+                write!(f, "{}({})", name, args.join(", "))
+            }
+            Expr::Binary { operator, left, right } => {
+                left.fmt_operand(f, *operator, false)?;
+                write!(f, " {} ", operator)?;
+                right.fmt_operand(f, *operator, true)
+            }
+            Expr::Negate(inner) => match **inner {
+                Expr::Binary { .. } | Expr::Negate(_) => write!(f, "-({})", inner),
+                _ => write!(f, "-{}", inner),
+            },
+            Expr::Default => write!(f, "DEFAULT"),
+        }
+    }
+}
+
+/// An arithmetic operator: `+`, `-`, `*`, `/` or `%`.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+pub enum ArithmeticOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+impl ArithmeticOp {
+    /// Multiplication, division and remainder bind tighter than addition and subtraction.
+    fn precedence(self) -> u8 {
+        match self {
+            ArithmeticOp::Add | ArithmeticOp::Subtract => 1,
+            ArithmeticOp::Multiply | ArithmeticOp::Divide | ArithmeticOp::Modulo => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for ArithmeticOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            ArithmeticOp::Add => "+",
+            ArithmeticOp::Subtract => "-",
+            ArithmeticOp::Multiply => "*",
+            ArithmeticOp::Divide => "/",
+            ArithmeticOp::Modulo => "%",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// An item of a SELECT list.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum SelectItem {
+    /// `*`, or `table.*` with the table name as written.
+    Wildcard(Option<String>),
+    /// An expression with its text as written, used as the column header unless it has an
+    /// `AS` alias.
+    Expr {
+        expr: Expr,
+        text: String,
+        alias: Option<String>,
+    },
+}
+
+/// A key of an ORDER BY clause.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SortKey {
+    pub expr: Expr,
+    pub descending: bool,
+    /// Whether NULLs come before all values; by default they sort as if larger than any value,
+    /// so they come last in ascending and first in descending order.
+    pub nulls_first: bool,
+}
+
+/// A WHERE or CHECK condition: predicates combined with AND, OR and NOT.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum WhereClause {
+    Predicate(Predicate),
+    And(Box<WhereClause>, Box<WhereClause>),
+    Or(Box<WhereClause>, Box<WhereClause>),
+    Not(Box<WhereClause>),
+}
+
+impl WhereClause {
+    /// Returns the predicates the condition is made of, from left to right.
+    pub fn predicates(&self) -> Vec<&Predicate> {
+        match self {
+            WhereClause::Predicate(predicate) => vec![predicate],
+            WhereClause::And(left, right) | WhereClause::Or(left, right) => {
+                let mut predicates = left.predicates();
+                predicates.extend(right.predicates());
+                predicates
+            }
+            WhereClause::Not(inner) => inner.predicates(),
+        }
+    }
+}
+
+/// A single test of a condition, e.g. `price * qty > 100`, `id IN (1, 2)` or
+/// `age NOT BETWEEN 18 AND 65`.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum Predicate {
+    Comparison {
+        left: Expr,
+        operator: ComparisonOp,
+        right: Expr,
+    },
+    /// `expr [NOT] IN (list)`
+    In {
+        expr: Expr,
+        list: Vec<Expr>,
+        negated: bool,
+    },
+    /// `expr [NOT] BETWEEN low AND high`
+    Between {
+        expr: Expr,
+        low: Expr,
+        high: Expr,
+        negated: bool,
+    },
+}
+
+impl Predicate {
+    /// Returns the expressions the predicate compares, from left to right.
+    pub fn operands(&self) -> Vec<&Expr> {
+        match self {
+            Predicate::Comparison { left, right, .. } => vec![left, right],
+            Predicate::In { expr, list, .. } => std::iter::once(expr).chain(list).collect(),
+            Predicate::Between { expr, low, high, .. } => vec![expr, low, high],
+        }
+    }
+}
+
+/// The operator of a comparison.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+pub enum ComparisonOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Like,
+    NotLike,
+}
+
+/// A constraint declared on the table as a whole rather than on a single column.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum TableConstraint {
+    /// `CHECK (condition)`; `expression` is the condition as written.
+    Check {
+        expression: String,
+        condition: WhereClause,
+    },
+    ForeignKey(ForeignKey),
+}
+
+/// `FOREIGN KEY (columns) REFERENCES parent_table (parent_columns) ON DELETE action`.
 #[derive(Debug, PartialEq, Clone, Serialize)]
-pub struct WhereClause {
-    pub column: String,
-    pub operator: String,
-    pub value: String,
+pub struct ForeignKey {
+    pub columns: Vec<String>,
+    pub parent_table: String,
+    /// Empty when the parent's primary key is referenced implicitly.
+    pub parent_columns: Vec<String>,
+    pub on_delete: ReferentialAction,
+}
+
+impl std::fmt::Display for ForeignKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FOREIGN KEY ({}) REFERENCES {}", self.columns.join(", "), self.parent_table)?;
+        if !self.parent_columns.is_empty() {
+            write!(f, " ({})", self.parent_columns.join(", "))?;
+        }
+        write!(f, " ON DELETE {}", self.on_delete)
+    }
+}
+
+/// What happens to referencing rows when the row they reference is deleted.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize)]
+pub enum ReferentialAction {
+    #[default]
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+}
+
+impl std::fmt::Display for ReferentialAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ReferentialAction::NoAction => "NO ACTION",
+            ReferentialAction::Restrict => "RESTRICT",
+            ReferentialAction::Cascade => "CASCADE",
+            ReferentialAction::SetNull => "SET NULL",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A change made by `ALTER TABLE`.
+#[derive(Debug, PartialEq)]
+pub enum AlterAction {
+    /// `ADD [COLUMN] definition`, with any constraints declared in the definition.
+    AddColumn {
+        column: Column,
+        constraints: Vec<TableConstraint>,
+    },
+    /// `DROP [COLUMN] name`
+    DropColumn { name: String },
+    /// `RENAME [COLUMN] from TO to`
+    RenameColumn { from: String, to: String },
+    /// `RENAME TO name`
+    RenameTable { to: String },
 }
 
 #[derive(Debug, PartialEq)]
@@ -12,25 +303,61 @@ pub enum Command {
     CreateTable {
         name: String,
         columns: Vec<Column>,
+        constraints: Vec<TableConstraint>,
+        if_not_exists: bool,
     },
     Insert {
         table: String,
-        values: Vec<String>,
+        /// Target columns; empty when the statement has no column list.
+        columns: Vec<String>,
+        values: Vec<Expr>,
     },
     Select {
         table: String,
-        columns: Vec<String>,
+        columns: Vec<SelectItem>,
         where_clause: Option<WhereClause>,
+        order_by: Vec<SortKey>,
     },
     Update {
         table: String,
         set_column: String,
-        set_value: String,
+        set_value: Expr,
+        where_clause: Option<WhereClause>,
+    },
+    Delete {
+        table: String,
         where_clause: Option<WhereClause>,
     },
     Truncate {
         table: String,
     },
+    /// `CREATE TABLE name AS SELECT columns FROM source [WHERE ...] [ORDER BY ...]`
+    CreateTableAs {
+        name: String,
+        if_not_exists: bool,
+        source: String,
+        columns: Vec<SelectItem>,
+        where_clause: Option<WhereClause>,
+        order_by: Vec<SortKey>,
+    },
+    DropTable {
+        name: String,
+        if_exists: bool,
+    },
+    AlterTable {
+        table: String,
+        action: AlterAction,
+    },
+    CreateSequence {
+        name: String,
+        start: i64,
+        increment: i64,
+        if_not_exists: bool,
+    },
+    DropSequence {
+        name: String,
+        if_exists: bool,
+    },
     Get {
         table: String,
         format: String,
@@ -39,307 +366,916 @@ pub enum Command {
     InspectTable {
         name: String,
     },
-    Unknown(String),
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize)]
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
 pub struct Column {
     pub name: String,
     pub data_type: String,
+    /// Parameters written in parentheses after the type name, e.g. `255` in `VARCHAR(255)`.
+    pub type_params: Vec<usize>,
+    /// Part of the table's primary key. A composite key is made of all flagged columns.
+    pub primary_key: bool,
+    pub not_null: bool,
+    pub unique: bool,
+    /// The DEFAULT expression, e.g. `'anon'` or `gen_random_uuid()`. Shown and stored in the
+    /// form its `Display` gives.
+    #[serde(serialize_with = "serialize_default")]
+    pub default: Option<Expr>,
+    /// Declared `SERIAL` or `AUTOINCREMENT`; the default draws from a sequence owned by the column.
+    pub auto_increment: bool,
+}
+
+/// Serializes a column default as its text, e.g. `"nextval('ids')"`.
+fn serialize_default<S: serde::Serializer>(default: &Option<Expr>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&default.as_ref().map_or_else(String::new, Expr::to_string))
+}
+
+impl Column {
+    /// Returns the full type as written in a column definition, e.g. `VARCHAR(255)`.
+    pub fn type_name(&self) -> String {
+        if self.type_params.is_empty() {
+            self.data_type.clone()
+        } else {
+            let params: Vec<String> = self.type_params.iter().map(|p| p.to_string()).collect();
+            format!("{}({})", self.data_type, params.join(","))
+        }
+    }
+
+    /// Returns the maximum number of characters a value may have, for CHAR and VARCHAR columns.
+    /// A CHAR column without a declared length holds a single character.
+    pub fn max_length(&self) -> Option<usize> {
+        match self.data_type.as_str() {
+            "VARCHAR" => self.type_params.first().copied(),
+            "CHAR" => Some(self.type_params.first().copied().unwrap_or(1)),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a type such as `INTEGER` or `VARCHAR(255)` into its upper-cased name and parameters,
+/// with `INT` read as `INTEGER`. Returns `None` if the parameter list is malformed.
+pub fn parse_data_type(input: &str) -> Option<(String, Vec<usize>)> {
+    let input = input.trim();
+    let (name, params) = match input.find('(') {
+        Some(open) => {
+            let params = input[open + 1..].trim_end().strip_suffix(')')?;
+            let params = params
+                .split(',')
+                .map(|p| p.trim().parse::<usize>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            (&input[..open], params)
+        }
+        None => (input, Vec::new()),
+    };
+
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    let name = match name.to_uppercase() {
+        name if name == "INT" => "INTEGER".to_string(),
+        name => name,
+    };
+    Some((name, params))
+}
+
+/// A single step of a JSON path: `->'key'` selects an object member, `->0` an array element.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum JsonPathStep {
+    Key(String),
+    Index(usize),
+}
+
+/// The JSON path operators following a column, e.g. `->'address'->>'city'`.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct JsonPath {
+    pub steps: Vec<JsonPathStep>,
+    /// True when the last operator is `->>`, which extracts the value as text instead of JSON.
+    pub as_text: bool,
+}
+
+impl std::fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            let arrow = if self.as_text && i + 1 == self.steps.len() { "->>" } else { "->" };
+            match step {
+                JsonPathStep::Key(key) => write!(f, "{}'{}'", arrow, key.replace('\'', "''"))?,
+                JsonPathStep::Index(index) => write!(f, "{}{}", arrow, index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An error from `Parser::parse`, locating where in the input the statement went wrong.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    /// Byte offset of the error in the input.
+    pub offset: usize,
+    /// Line of the error, starting at 1.
+    pub line: usize,
+    /// Column of the error in characters, starting at 1.
+    pub column: usize,
+    /// The token at the error, or an empty string at the end of the input.
+    pub found: String,
+    /// What the grammar would have accepted at the error, e.g. `FROM`, `'('` or `identifier`.
+    pub expected: Vec<String>,
+    /// Explains an error the grammar cannot express, such as a second primary key.
+    pub message: Option<String>,
+}
+
+impl ParseError {
+    fn new(input: &str, offset: usize, expected: Vec<String>, message: Option<String>) -> Self {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            found: token_at(&input[offset..]).to_string(),
+            expected,
+            message,
+        }
+    }
+
+    /// Makes an error for a statement that matches the grammar but is invalid, pointing at `pair`.
+    fn invalid(pair: &Pair<Rule>, message: impl Into<String>) -> Self {
+        ParseError::new(pair.get_input(), pair.as_span().start(), Vec::new(), Some(message.into()))
+    }
+
+    /// Moves an error in a statement starting at byte `start` of `script` to its place in the script.
+    fn within(self, script: &str, start: usize) -> Self {
+        ParseError::new(script, start + self.offset, self.expected, self.message)
+    }
+
+    fn from_pest(input: &str, error: pest::error::Error<Rule>) -> Self {
+        let mut offset = match error.location {
+            InputLocation::Pos(pos) => pos,
+            InputLocation::Span((start, _)) => start,
+        };
+        let mut expected: Vec<String> = match &error.variant {
+            ErrorVariant::ParsingError { positives, .. } => positives.iter().filter_map(|r| describe_rule(*r)).collect(),
+            ErrorVariant::CustomError { .. } => Vec::new(),
+        };
+
+        // The detailed attempts also track literals such as `(`, so they can reach further into
+        // the input than the rules pest reports by default
+        if let Some(attempts) = error.parse_attempts().filter(|a| a.max_position >= offset) {
+            let mut attempted: Vec<String> = attempts
+                .call_stacks()
+                .iter()
+                .filter_map(|stack| stack.deepest.get_rule().or(stack.parent.as_ref()))
+                .filter_map(|rule| describe_rule(*rule))
+                .collect();
+            let mut operand_complete = false;
+            for token in attempts.expected_tokens().iter().map(|t| t.to_string()) {
+                // An arithmetic operator can only follow a complete operand, so the value pest
+                // reports for the surrounding expression is not what is missing
+                if matches!(token.as_str(), "+" | "*" | "/" | "%") {
+                    operand_complete = true;
+                    attempted.push("operator".to_string());
+                    continue;
+                }
+                if token.starts_with("->") {
+                    attempted.push("'->'".to_string());
+                    continue;
+                }
+                // Quotes, `_` and `-` are only tried inside identifiers, strings and numbers, and
+                // `/*` is the start of a comment
+                let punctuation = !token.is_empty() && token.chars().all(|c| c.is_ascii_punctuation());
+                if punctuation && !token.contains(['\'', '"', '_', '-']) && token != "/*" {
+                    attempted.push(format!("'{}'", token));
+                }
+            }
+            if operand_complete {
+                attempted.retain(|e| e != "value");
+            }
+            if !attempted.is_empty() || attempts.max_position > offset {
+                offset = attempts.max_position;
+                expected = attempted;
+            }
+        }
+        expected.sort();
+        expected.dedup();
+
+        ParseError::new(input, offset, expected, None)
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(message) = &self.message {
+            return write!(f, "{} at line {}, column {}", message, self.line, self.column);
+        }
+
+        write!(f, "Syntax error at line {}, column {}: ", self.line, self.column)?;
+        match self.expected.split_last() {
+            Some((last, [])) => write!(f, "expected {}, ", last)?,
+            Some((last, rest)) => write!(f, "expected {} or {}, ", rest.join(", "), last)?,
+            None => {}
+        }
+        if self.found.is_empty() {
+            write!(f, "found end of input")
+        } else {
+            write!(f, "found '{}'", self.found)
+        }
+    }
+}
+
+/// Returns the token starting `input`: a word, a number, or a single other character.
+fn token_at(input: &str) -> &str {
+    let word_len = input.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(input.len());
+    match input.chars().next() {
+        Some(_) if word_len > 0 => &input[..word_len],
+        Some(c) => &input[..c.len_utf8()],
+        None => "",
+    }
+}
+
+/// Names a rule for the expected tokens of a parse error; `None` for rules the user never sees.
+fn describe_rule(rule: Rule) -> Option<String> {
+    let name = format!("{:?}", rule);
+    if let Some(keyword) = name.strip_prefix("kw_") {
+        return Some(keyword.to_uppercase());
+    }
+    let description = match rule {
+        Rule::identifier | Rule::column_ref => "identifier",
+        Rule::type_name | Rule::data_type => "type",
+        Rule::string => "string",
+        Rule::number | Rule::integer | Rule::unsigned_integer => "number",
+        Rule::literal | Rule::expr | Rule::term | Rule::factor | Rule::value | Rule::function_call => "value",
+        Rule::json_arrow => "'->'",
+        Rule::star => "'*'",
+        Rule::EOI => "end of input",
+        _ => return None,
+    };
+    Some(description.to_string())
 }
 
 pub struct Parser {}
 
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Parser {
     pub fn new() -> Self {
+        // Have pest record the literal tokens it tried, so that parse errors can list punctuation
+        pest::set_error_detail(true);
         Parser {}
     }
 
-    pub fn parse(&self, input: &str) -> Command {
-        let input = input.trim();
-        let input_upper = input.to_uppercase();
-
-        if input_upper.starts_with("CREATE TABLE") {
-            self.parse_create_table(input)
-        } else if input_upper.starts_with("INSERT INTO") {
-            self.parse_insert(input)
-        } else if input_upper.starts_with("SELECT") {
-            self.parse_select(input)
-        } else if input_upper.starts_with("UPDATE") {
-            self.parse_update(input)
-        } else if input_upper.starts_with("TRUNCATE TABLE") {
-            self.parse_truncate(input)
-        } else if input_upper.starts_with("GET") {
-            self.parse_get(input)
-        } else if input_upper.starts_with("SHOW TABLES") {
-            Command::ShowTables
-        } else if input_upper.starts_with("INSPECT") {
-            self.parse_inspect(input)
-        } else {
-            Command::Unknown(input.to_string())
-        }
-    }
-
-    /// Parses a simple WHERE clause with operators =, !=, <, >, <=, >=, LIKE, and NOT LIKE.
-    fn parse_where_clause(&self, where_str: &str) -> Option<WhereClause> {
-        let where_upper = where_str.to_uppercase();
-        let operator_str;
-        let operator_len;
-
-        if where_upper.contains("NOT LIKE") {
-            operator_str = "NOT LIKE";
-            operator_len = 8;
-        } else if where_upper.contains("LIKE") {
-            operator_str = "LIKE";
-            operator_len = 4;
-        } else if where_upper.contains("<=") {
-            operator_str = "<=";
-            operator_len = 2;
-        } else if where_upper.contains(">=") {
-            operator_str = ">=";
-            operator_len = 2;
-        } else if where_upper.contains("!=") {
-            operator_str = "!=";
-            operator_len = 2;
-        } else if where_upper.contains('<') {
-            operator_str = "<";
-            operator_len = 1;
-        } else if where_upper.contains('>') {
-            operator_str = ">";
-            operator_len = 1;
-        } else if where_upper.contains('=') {
-            operator_str = "=";
-            operator_len = 1;
-        } else {
-            return None; // No supported operator found
-        };
-
-        if let Some(op_pos) = where_upper.find(operator_str) {
-            let column = where_str[..op_pos].trim().to_string();
-            let value = where_str[op_pos + operator_len..].trim().trim_matches('"').trim_matches('\'').to_string();
-            Some(WhereClause {
-                column,
-                operator: operator_str.to_string(),
-                value,
-            })
-        } else {
-            None // Should not happen if we found the operator string
+    /// Parses a single statement, optionally terminated by a semicolon. Fails on input that does
+    /// not match the grammar in `sql.pest`, or breaks a rule the grammar cannot express such as
+    /// declaring two primary keys.
+    pub fn parse(&self, input: &str) -> Result<Command, ParseError> {
+        let mut pairs = SqlGrammar::parse(Rule::statement_input, input).map_err(|e| ParseError::from_pest(input, e))?;
+        match pairs.next() {
+            Some(statement) => self.parse_statement(statement),
+            None => Err(ParseError::new(input, 0, Vec::new(), Some("Empty statement".to_string()))),
         }
     }
 
-    fn parse_create_table(&self, input: &str) -> Command {
-        // Format: CREATE TABLE name (col1 TYPE, col2 TYPE)
-        let input_upper = input.to_uppercase();
-        let rest = match input_upper.strip_prefix("CREATE TABLE") {
-            Some(r) => r.trim(),
-            None => return Command::Unknown(input.to_string()),
-        };
+    /// Parses a script of statements separated by semicolons, giving a result per statement.
+    /// Statements holding nothing but whitespace and comments are skipped. Error positions are
+    /// relative to the whole script.
+    pub fn parse_script(&self, script: &str) -> Vec<Result<Command, ParseError>> {
+        self.split_script(script)
+            .into_iter()
+            .map(|(start, statement)| self.parse(statement).map_err(|e| e.within(script, start)))
+            .collect()
+    }
 
-        // Find the opening parenthesis
-        let parts: Vec<&str> = rest.splitn(2, '(').collect();
-        if parts.len() != 2 {
-            return Command::Unknown(input.to_string());
-        }
-
-        let table_name = parts[0].trim().to_string();
-        let columns_str = parts[1].trim_end_matches(')').trim();
-
-        // Parse columns: "col1 TYPE, col2 TYPE"
-        let columns: Vec<Column> = columns_str
-            .split(',')
-            .filter_map(|col| {
-                let parts: Vec<&str> = col.trim().split_whitespace().collect();
-                if parts.len() >= 2 {
-                    Some(Column {
-                        name: parts[0].to_string(),
-                        data_type: parts[1].to_uppercase(),
-                    })
-                } else if parts.len() == 1 && !parts[0].is_empty() {
-                    // Default to TEXT if no type specified
-                    Some(Column {
-                        name: parts[0].to_string(),
-                        data_type: "TEXT".to_string(),
-                    })
-                } else {
-                    None
-                }
-            })
+    /// Splits a script into its statements, each with its byte offset in the script, leaving out
+    /// the semicolons and any statement holding nothing but whitespace and comments.
+    pub fn split_script<'a>(&self, script: &'a str) -> Vec<(usize, &'a str)> {
+        statement_texts(script)
+            .into_iter()
+            .filter(|(_, text)| !is_blank(text))
+            .collect()
+    }
+
+    /// Returns true if `script` is ready to run: its last statement is terminated by a semicolon,
+    /// or all of its statements parse. Used by the CLI to decide whether to read more lines.
+    pub fn is_complete(&self, script: &str) -> bool {
+        let texts = statement_texts(script);
+        let terminated = texts.len() > 1 && texts.last().is_some_and(|(_, text)| is_blank(text));
+        terminated || self.parse_script(script).iter().all(|result| result.is_ok())
+    }
+
+    /// Parses the body of a CHECK constraint, e.g. `(age >= 0)`.
+    pub fn parse_check(&self, body: &str) -> Option<TableConstraint> {
+        let condition = SqlGrammar::parse(Rule::check_input, body.trim()).ok()?.next()?;
+        self.parse_check_condition(condition).ok()
+    }
+
+    /// Rewrites the condition of a CHECK constraint so that its references to the column stored
+    /// as `from` refer to `to` instead, written as given. `None` if the condition does not parse.
+    pub fn rename_column_in_check(&self, expression: &str, from: &str, to: &str) -> Option<String> {
+        let pairs = SqlGrammar::parse(Rule::check_input, expression).ok()?;
+        let references: Vec<_> = pairs
+            .flatten()
+            .filter(|p| p.as_rule() == Rule::column_ref)
+            .filter_map(|p| child(&p, Rule::identifier))
+            .filter(|i| identifier_matches(from, i.as_str()))
+            .map(|i| i.as_span())
             .collect();
 
-        Command::CreateTable {
-            name: table_name,
-            columns,
+        // Replace from the end so that the earlier spans stay valid
+        let mut renamed = expression.to_string();
+        for span in references.iter().rev() {
+            renamed.replace_range(span.start()..span.end(), to);
+        }
+        Some(renamed)
+    }
+
+    /// Parses a single expression, such as the DEFAULT of a column, e.g. `nextval('ids')`.
+    pub fn parse_expression(&self, input: &str) -> Option<Expr> {
+        let expr = SqlGrammar::parse(Rule::expression_input, input.trim()).ok()?.next()?;
+        Some(build_expr(expr))
+    }
+
+    fn parse_statement(&self, statement: Pair<Rule>) -> Result<Command, ParseError> {
+        match statement.as_rule() {
+            Rule::create_table => self.parse_create_table(statement),
+            Rule::create_table_as => self.parse_create_table_as(statement),
+            Rule::drop_table => Ok(Command::DropTable {
+                name: identifier(&statement)?,
+                if_exists: child(&statement, Rule::if_exists).is_some(),
+            }),
+            Rule::alter_table => self.parse_alter_table(statement),
+            Rule::create_sequence => self.parse_create_sequence(statement),
+            Rule::drop_sequence => Ok(Command::DropSequence {
+                name: identifier(&statement)?,
+                if_exists: child(&statement, Rule::if_exists).is_some(),
+            }),
+            Rule::insert => self.parse_insert(statement),
+            Rule::select => self.parse_select(statement),
+            Rule::update => self.parse_update(statement),
+            Rule::delete => Ok(Command::Delete {
+                table: identifier(&statement)?,
+                where_clause: self.parse_where_clause(&statement)?,
+            }),
+            Rule::truncate => Ok(Command::Truncate { table: identifier(&statement)? }),
+            Rule::get => self.parse_get(statement),
+            Rule::show_tables => Ok(Command::ShowTables),
+            Rule::inspect => Ok(Command::InspectTable { name: identifier(&statement)? }),
+            _ => Err(ParseError::invalid(&statement, "Unsupported statement")),
+        }
+    }
+
+    /// Parses the optional WHERE clause of a statement.
+    fn parse_where_clause(&self, statement: &Pair<Rule>) -> Result<Option<WhereClause>, ParseError> {
+        match child(statement, Rule::where_clause) {
+            Some(where_clause) => Ok(Some(self.parse_condition(required(&where_clause, Rule::condition)?)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses a condition: comparisons combined with AND, OR, NOT and parentheses.
+    fn parse_condition(&self, condition: Pair<Rule>) -> Result<WhereClause, ParseError> {
+        match condition.as_rule() {
+            Rule::condition | Rule::conjunction => {
+                let combine = if condition.as_rule() == Rule::condition { WhereClause::Or } else { WhereClause::And };
+                let mut operands = condition.clone().into_inner().filter(|p| !matches!(p.as_rule(), Rule::kw_or | Rule::kw_and));
+                let first = operands.next().ok_or_else(|| missing(&condition, Rule::predicate))?;
+                let mut clause = self.parse_condition(first)?;
+                for operand in operands {
+                    clause = combine(Box::new(clause), Box::new(self.parse_condition(operand)?));
+                }
+                Ok(clause)
+            }
+            Rule::negation => {
+                let negations = children(&condition, Rule::kw_not).count();
+                let inner = condition.clone().into_inner().last().ok_or_else(|| missing(&condition, Rule::predicate))?;
+                let mut clause = self.parse_condition(inner)?;
+                for _ in 0..negations {
+                    clause = WhereClause::Not(Box::new(clause));
+                }
+                Ok(clause)
+            }
+            _ => Ok(WhereClause::Predicate(self.parse_predicate(condition)?)),
         }
     }
 
-    fn parse_insert(&self, input: &str) -> Command {
-        // Format: INSERT INTO table VALUES (val1, val2)
-        let input_upper = input.to_uppercase();
-        if !input_upper.starts_with("INSERT INTO") {
-            return Command::Unknown(input.to_string());
+    /// Parses a comparison with operators =, !=, <, >, <=, >=, LIKE and NOT LIKE, or a
+    /// [NOT] IN or [NOT] BETWEEN test.
+    fn parse_predicate(&self, predicate: Pair<Rule>) -> Result<Predicate, ParseError> {
+        let expr = build_expr(required(&predicate, Rule::expr)?);
+
+        // Format: column [NOT] IN (val1, val2, ...)
+        if let Some(in_list) = child(&predicate, Rule::in_list) {
+            return Ok(Predicate::In {
+                expr,
+                list: children(&in_list, Rule::expr).map(build_expr).collect(),
+                negated: child(&in_list, Rule::kw_not).is_some(),
+            });
         }
 
-        let after_insert = &input[11..].trim_start(); // Skip "INSERT INTO" (11 chars)
-        let values_pos_original = match after_insert.to_uppercase().find("VALUES") {
-            Some(pos) => pos,
-            None => return Command::Unknown(input.to_string()),
+        // Format: column [NOT] BETWEEN low AND high
+        if let Some(between) = child(&predicate, Rule::between) {
+            let mut bounds = children(&between, Rule::expr);
+            return Ok(Predicate::Between {
+                expr,
+                low: build_expr(bounds.next().ok_or_else(|| missing(&between, Rule::expr))?),
+                high: build_expr(bounds.next().ok_or_else(|| missing(&between, Rule::expr))?),
+                negated: child(&between, Rule::kw_not).is_some(),
+            });
+        }
+
+        let comparison = required(&predicate, Rule::comparison)?;
+        let operator = required(&comparison, Rule::comparison_op)?;
+        let operator = if child(&operator, Rule::kw_not).is_some() {
+            ComparisonOp::NotLike
+        } else if child(&operator, Rule::kw_like).is_some() {
+            ComparisonOp::Like
+        } else {
+            match operator.as_str() {
+                "=" => ComparisonOp::Equal,
+                "!=" => ComparisonOp::NotEqual,
+                "<" => ComparisonOp::Less,
+                "<=" => ComparisonOp::LessOrEqual,
+                ">" => ComparisonOp::Greater,
+                ">=" => ComparisonOp::GreaterOrEqual,
+                other => return Err(ParseError::invalid(&operator, format!("Unsupported operator '{}'", other))),
+            }
         };
-        
-        let table_name = after_insert[..values_pos_original].trim().to_string();
-        let values_str = after_insert[values_pos_original + 6..].trim().trim_start_matches('(').trim_end_matches(')');
 
-        let values: Vec<String> = values_str
-            .split(',')
-            .map(|v| v.trim().trim_matches('"').trim_matches('"').to_string())
-            .collect();
+        Ok(Predicate::Comparison {
+            left: expr,
+            operator,
+            right: build_expr(required(&comparison, Rule::expr)?),
+        })
+    }
+
+    /// Makes a CHECK constraint of a condition, keeping the condition as written.
+    fn parse_check_condition(&self, condition: Pair<Rule>) -> Result<TableConstraint, ParseError> {
+        Ok(TableConstraint::Check {
+            expression: condition.as_str().trim().to_string(),
+            condition: self.parse_condition(condition)?,
+        })
+    }
+
+    fn parse_create_table(&self, statement: Pair<Rule>) -> Result<Command, ParseError> {
+        // Format: CREATE TABLE [IF NOT EXISTS] name (col1 TYPE, col2 TYPE, PRIMARY KEY (a, b), ...)
+        let mut columns: Vec<Column> = Vec::new();
+        let mut constraints = Vec::new();
+        let mut table_primary_key = None;
+        let mut column_primary_key = None;
+        for element in statement.clone().into_inner() {
+            match element.as_rule() {
+                Rule::primary_key_constraint => {
+                    if table_primary_key.is_some() {
+                        return Err(ParseError::invalid(&element, "Multiple primary keys are not allowed"));
+                    }
+                    table_primary_key = Some((identifier_list(&element)?, element));
+                }
+                Rule::foreign_key_constraint => {
+                    let references = required(&element, Rule::references)?;
+                    constraints.push(self.parse_references(identifier_list(&element)?, references)?);
+                }
+                Rule::check_constraint => {
+                    constraints.push(self.parse_check_condition(required(&element, Rule::condition)?)?);
+                }
+                Rule::column_def => {
+                    let (column, column_constraints) = self.parse_column_definition(element.clone())?;
+                    if column.primary_key {
+                        if column_primary_key.is_some() {
+                            return Err(ParseError::invalid(&element, "Multiple primary keys are not allowed"));
+                        }
+                        column_primary_key = Some(element);
+                    }
+                    columns.push(column);
+                    constraints.extend(column_constraints);
+                }
+                _ => {}
+            }
+        }
 
-        Command::Insert {
-            table: table_name,
-            values,
+        // A table has at most one primary key, declared either on columns or as a table constraint
+        if let Some((key_columns, element)) = table_primary_key {
+            if column_primary_key.is_some() {
+                return Err(ParseError::invalid(&element, "Multiple primary keys are not allowed"));
+            }
+            for key_column in key_columns {
+                match columns.iter_mut().find(|c| identifier_matches(&identifier_name(&c.name), &key_column)) {
+                    Some(column) => column.primary_key = true,
+                    None => {
+                        let message = format!("Primary key column '{}' is not defined", key_column);
+                        return Err(ParseError::invalid(&element, message));
+                    }
+                }
+            }
         }
+
+        Ok(Command::CreateTable {
+            name: identifier(&statement)?,
+            columns,
+            constraints,
+            if_not_exists: child(&statement, Rule::if_not_exists).is_some(),
+        })
     }
 
-    fn parse_select(&self, input: &str) -> Command {
-        // Format: SELECT col1, col2 FROM table WHERE col = val
-        let input_upper = input.to_uppercase();
-        let after_select = &input[6..].trim_start(); // Skip "SELECT "
-        let after_select_upper = &input_upper[6..].trim_start();
+    fn parse_create_table_as(&self, statement: Pair<Rule>) -> Result<Command, ParseError> {
+        // Format: CREATE TABLE [IF NOT EXISTS] name AS SELECT ...
+        match self.parse_select(required(&statement, Rule::select)?)? {
+            Command::Select { table, columns, where_clause, order_by } => Ok(Command::CreateTableAs {
+                name: identifier(&statement)?,
+                if_not_exists: child(&statement, Rule::if_not_exists).is_some(),
+                source: table,
+                columns,
+                where_clause,
+                order_by,
+            }),
+            _ => Err(ParseError::invalid(&statement, "Expected a SELECT statement")),
+        }
+    }
 
-        let from_pos = match after_select_upper.find("FROM ") {
-            Some(pos) => pos,
-            None => return Command::Unknown(input.to_string()),
+    /// Parses a column definition such as `name VARCHAR(20) NOT NULL DEFAULT 'anon'`, returning
+    /// the column and any CHECK or REFERENCES constraints declared on it.
+    /// The type defaults to TEXT when omitted.
+    fn parse_column_definition(&self, definition: Pair<Rule>) -> Result<(Column, Vec<TableConstraint>), ParseError> {
+        // The name is kept as written; quotes are resolved when the column is created
+        let mut column = Column {
+            name: identifier(&definition)?,
+            data_type: "TEXT".to_string(),
+            ..Default::default()
         };
 
-        let columns_str = after_select[..from_pos].trim();
-        let after_from = &after_select[from_pos + 5..].trim_start(); // Skip "FROM "
-        let after_from_upper = &after_select_upper[from_pos + 5..].trim_start();
+        if let Some(data_type) = child(&definition, Rule::data_type) {
+            let (data_type_name, type_params) = parse_data_type(data_type.as_str())
+                .ok_or_else(|| ParseError::invalid(&data_type, "Invalid type"))?;
+            if data_type_name == "SERIAL" {
+                column.data_type = "INTEGER".to_string();
+                column.auto_increment = true;
+            } else {
+                column.data_type = data_type_name;
+                column.type_params = type_params;
+            }
+        }
 
-        let where_pos = after_from_upper.find("WHERE ");
+        // Column constraints follow the type
+        let mut constraints = Vec::new();
+        for constraint in definition.into_inner() {
+            match constraint.as_rule() {
+                Rule::primary_key => column.primary_key = true,
+                Rule::not_null => column.not_null = true,
+                Rule::unique => column.unique = true,
+                Rule::autoincrement => column.auto_increment = true,
+                Rule::default_clause => {
+                    column.default = Some(build_expr(required(&constraint, Rule::expr)?));
+                }
+                Rule::check_constraint => {
+                    constraints.push(self.parse_check_condition(required(&constraint, Rule::condition)?)?);
+                }
+                Rule::references => {
+                    constraints.push(self.parse_references(vec![column.name.clone()], constraint)?);
+                }
+                _ => {}
+            }
+        }
 
-        let (table_name, where_clause) = if let Some(pos) = where_pos {
-            let table_part = &after_from[..pos].trim();
-            let where_part = &after_from[pos + 6..].trim(); // Skip "WHERE "
-            (table_part.to_string(), self.parse_where_clause(where_part))
-        } else {
-            (after_from.to_string(), None)
+        Ok((column, constraints))
+    }
+
+    /// Parses `REFERENCES parent [(col, ...)] [ON DELETE action]` into a foreign key on `columns`.
+    fn parse_references(&self, columns: Vec<String>, references: Pair<Rule>) -> Result<TableConstraint, ParseError> {
+        let parent_columns = match child(&references, Rule::identifier_list) {
+            Some(_) => identifier_list(&references)?,
+            None => Vec::new(),
         };
+        if !parent_columns.is_empty() && parent_columns.len() != columns.len() {
+            let message = format!(
+                "Foreign key has {} columns but references {} columns",
+                columns.len(),
+                parent_columns.len()
+            );
+            return Err(ParseError::invalid(&references, message));
+        }
 
-        let columns: Vec<String> = if columns_str == "*" {
-            vec!["*".to_string()]
-        } else {
-            columns_str
-                .split(',')
-                .map(|c| c.trim().to_string())
-                .collect()
+        let on_delete = match child(&references, Rule::referential_action).and_then(|a| a.into_inner().next()) {
+            Some(action) => match action.as_rule() {
+                Rule::kw_cascade => ReferentialAction::Cascade,
+                Rule::kw_restrict => ReferentialAction::Restrict,
+                Rule::kw_set => ReferentialAction::SetNull,
+                _ => ReferentialAction::NoAction,
+            },
+            None => ReferentialAction::NoAction,
         };
 
-        Command::Select {
-            table: table_name,
+        Ok(TableConstraint::ForeignKey(ForeignKey {
             columns,
-            where_clause,
-        }
+            parent_table: identifier(&references)?,
+            parent_columns,
+            on_delete,
+        }))
     }
 
-    fn parse_update(&self, input: &str) -> Command {
-        // Format: UPDATE table SET col = val WHERE other_col = other_val
-        let input_upper = input.to_uppercase();
-    
-        let set_pos = match input_upper.find(" SET ") {
-            Some(pos) => pos,
-            None => return Command::Unknown(input.to_string()),
+    fn parse_insert(&self, statement: Pair<Rule>) -> Result<Command, ParseError> {
+        // Format: INSERT INTO table [(col1, col2)] VALUES (val1, val2)
+        let columns = match child(&statement, Rule::identifier_list) {
+            Some(_) => identifier_list(&statement)?,
+            None => Vec::new(),
         };
-    
-        // "UPDATE ".len() is 7
-        let table_name = input[7..set_pos].trim().to_string();
-        // " SET ".len() is 5
-        let after_set = &input[set_pos + 5..];
-        let after_set_upper = &input_upper[set_pos + 5..];
-    
-        let where_pos = after_set_upper.find(" WHERE ");
-    
-        let (set_part, where_clause) = if let Some(pos) = where_pos {
-            // " WHERE ".len() is 7
-            let where_part_str = &after_set[pos + 7..].trim();
-            (
-                after_set[..pos].trim(),
-                self.parse_where_clause(where_part_str),
-            )
-        } else {
-            (after_set.trim(), None)
-        };
-    
-        // Parse SET part: "col = val"
-        let set_parts: Vec<&str> = set_part.split('=').map(|s| s.trim()).collect();
-        if set_parts.len() != 2 {
-            return Command::Unknown(format!("Invalid SET clause: {}", set_part));
-        }
-        let set_column = set_parts[0].to_string();
-        let set_value = set_parts[1].trim_matches('"').trim_matches('"').to_string();
-    
-        Command::Update {
-            table: table_name,
-            set_column,
-            set_value,
-            where_clause,
-        }
-    }
-
-    fn parse_truncate(&self, input: &str) -> Command {
-        // Format: TRUNCATE TABLE table_name
-        let input_upper = input.to_uppercase();
-        let rest = match input_upper.strip_prefix("TRUNCATE TABLE") {
-            Some(r) => r.trim(),
-            None => return Command::Unknown(input.to_string()),
+        let values = required(&statement, Rule::values)?;
+
+        Ok(Command::Insert {
+            table: identifier(&statement)?,
+            columns,
+            values: children(&values, Rule::value).map(build_value).collect(),
+        })
+    }
+
+    fn parse_select(&self, statement: Pair<Rule>) -> Result<Command, ParseError> {
+        // Format: SELECT expr [AS alias], table.*, ... FROM table WHERE condition ORDER BY expr DESC, ...
+        let select_list = required(&statement, Rule::select_list)?;
+        let columns = children(&select_list, Rule::select_item)
+            .map(|item| {
+                if child(&item, Rule::star).is_some() {
+                    return Ok(SelectItem::Wildcard(None));
+                }
+                if let Some(table_star) = child(&item, Rule::table_star) {
+                    return Ok(SelectItem::Wildcard(Some(identifier(&table_star)?)));
+                }
+                let expr = required(&item, Rule::expr)?;
+                Ok(SelectItem::Expr {
+                    text: expr.as_str().trim().to_string(),
+                    expr: build_expr(expr),
+                    alias: child(&item, Rule::identifier).map(|a| a.as_str().to_string()),
+                })
+            })
+            .collect::<Result<_, ParseError>>()?;
+
+        let order_by = match child(&statement, Rule::order_by) {
+            Some(order_by) => children(&order_by, Rule::sort_key).map(build_sort_key).collect::<Result<_, _>>()?,
+            None => Vec::new(),
         };
 
-        if rest.is_empty() {
-            return Command::Unknown(input.to_string());
-        }
+        Ok(Command::Select {
+            table: identifier(&statement)?,
+            columns,
+            where_clause: self.parse_where_clause(&statement)?,
+            order_by,
+        })
+    }
 
-        Command::Truncate {
-            table: rest.to_string(),
-        }
+    fn parse_update(&self, statement: Pair<Rule>) -> Result<Command, ParseError> {
+        // Format: UPDATE table SET col = val [WHERE other_col = other_val]
+        let mut identifiers = children(&statement, Rule::identifier).map(|i| i.as_str().to_string());
+
+        Ok(Command::Update {
+            table: identifiers.next().ok_or_else(|| missing(&statement, Rule::identifier))?,
+            set_column: identifiers.next().ok_or_else(|| missing(&statement, Rule::identifier))?,
+            set_value: build_value(required(&statement, Rule::value)?),
+            where_clause: self.parse_where_clause(&statement)?,
+        })
     }
 
-    fn parse_get(&self, input: &str) -> Command {
-        // Format: GET <tablename> AS JSON
-        let input_upper = input.to_uppercase();
-        let rest = match input_upper.strip_prefix("GET") {
-            Some(r) => r.trim(),
-            None => return Command::Unknown(input.to_string()),
+    fn parse_alter_table(&self, statement: Pair<Rule>) -> Result<Command, ParseError> {
+        // Format: ALTER TABLE name ADD [COLUMN] definition | DROP [COLUMN] col | RENAME [COLUMN] a TO b
+        //         | RENAME TO new_name
+        let change = statement.clone().into_inner().last().ok_or_else(|| missing(&statement, Rule::alter_table))?;
+        let action = match change.as_rule() {
+            Rule::add_column => {
+                let (column, constraints) = self.parse_column_definition(required(&change, Rule::column_def)?)?;
+                AlterAction::AddColumn { column, constraints }
+            }
+            Rule::drop_column => AlterAction::DropColumn { name: identifier(&change)? },
+            Rule::rename_table => AlterAction::RenameTable { to: identifier(&change)? },
+            Rule::rename_column => {
+                let mut identifiers = children(&change, Rule::identifier).map(|i| i.as_str().to_string());
+                AlterAction::RenameColumn {
+                    from: identifiers.next().ok_or_else(|| missing(&change, Rule::identifier))?,
+                    to: identifiers.next().ok_or_else(|| missing(&change, Rule::identifier))?,
+                }
+            }
+            _ => return Err(ParseError::invalid(&change, "Unsupported ALTER TABLE action")),
         };
 
-        let parts: Vec<&str> = rest.split(" AS ").collect();
-        if parts.len() != 2 {
-            return Command::Unknown(input.to_string());
+        Ok(Command::AlterTable {
+            table: identifier(&statement)?,
+            action,
+        })
+    }
+
+    fn parse_create_sequence(&self, statement: Pair<Rule>) -> Result<Command, ParseError> {
+        // Format: CREATE SEQUENCE [IF NOT EXISTS] name [START [WITH] n] [INCREMENT [BY] n]
+        let mut start = 1;
+        let mut increment = 1;
+        for option in children(&statement, Rule::sequence_option) {
+            let integer = required(&option, Rule::integer)?;
+            let value = integer
+                .as_str()
+                .parse::<i64>()
+                .map_err(|_| ParseError::invalid(&integer, "Number out of range"))?;
+            if child(&option, Rule::kw_start).is_some() {
+                start = value;
+            } else if value == 0 {
+                return Err(ParseError::invalid(&integer, "INCREMENT must not be zero"));
+            } else {
+                increment = value;
+            }
         }
 
-        let table_name = parts[0].trim().to_string();
-        let format = parts[1].trim().to_string();
+        Ok(Command::CreateSequence {
+            name: identifier(&statement)?,
+            start,
+            increment,
+            if_not_exists: child(&statement, Rule::if_not_exists).is_some(),
+        })
+    }
 
-        if format.to_uppercase() == "JSON" {
-            Command::Get {
-                table: table_name,
-                format,
-            }
+    fn parse_get(&self, statement: Pair<Rule>) -> Result<Command, ParseError> {
+        // Format: GET <tablename> AS JSON
+        let mut identifiers = children(&statement, Rule::identifier);
+        let table = identifiers.next().ok_or_else(|| missing(&statement, Rule::identifier))?;
+        let format = identifiers.next().ok_or_else(|| missing(&statement, Rule::identifier))?;
+
+        if format.as_str().eq_ignore_ascii_case("JSON") {
+            Ok(Command::Get {
+                table: table.as_str().to_string(),
+                format: "JSON".to_string(),
+            })
         } else {
-            Command::Unknown(format!("Unsupported format: {}", format))
+            Err(ParseError::invalid(&format, format!("Unsupported format: {}", format.as_str())))
         }
     }
+}
 
-    fn parse_inspect(&self, input: &str) -> Command {
-        let input_upper = input.to_uppercase();
-        let rest = match input_upper.strip_prefix("INSPECT") {
-            Some(r) => r.trim(),
-            None => return Command::Unknown(input.to_string()),
-        };
+/// Splits `script` at the semicolons outside strings, quoted identifiers and comments.
+fn statement_texts(script: &str) -> Vec<(usize, &str)> {
+    match SqlGrammar::parse(Rule::script_input, script) {
+        Ok(pairs) => pairs
+            .filter(|p| p.as_rule() == Rule::statement_text)
+            .map(|p| (p.as_span().start(), p.as_str()))
+            .collect(),
+        Err(_) => vec![(0, script)],
+    }
+}
+
+/// Returns true if `text` holds nothing but whitespace and comments.
+fn is_blank(text: &str) -> bool {
+    SqlGrammar::parse(Rule::empty_input, text).is_ok()
+}
 
-        if rest.is_empty() {
-            return Command::Unknown(input.to_string());
+/// Returns the first direct child of `pair` matching `rule`.
+fn child<'i>(pair: &Pair<'i, Rule>, rule: Rule) -> Option<Pair<'i, Rule>> {
+    children(pair, rule).next()
+}
+
+/// Returns the first direct child of `pair` matching `rule`, which the grammar requires.
+fn required<'i>(pair: &Pair<'i, Rule>, rule: Rule) -> Result<Pair<'i, Rule>, ParseError> {
+    child(pair, rule).ok_or_else(|| missing(pair, rule))
+}
+
+/// Makes the error for a child the grammar requires but `pair` lacks.
+fn missing(pair: &Pair<Rule>, rule: Rule) -> ParseError {
+    let expected = describe_rule(rule).into_iter().collect();
+    ParseError::new(pair.get_input(), pair.as_span().start(), expected, None)
+}
+
+/// Returns the direct children of `pair` matching `rule`.
+fn children<'i>(pair: &Pair<'i, Rule>, rule: Rule) -> impl Iterator<Item = Pair<'i, Rule>> {
+    pair.clone().into_inner().filter(move |p| p.as_rule() == rule)
+}
+
+/// Returns the first identifier directly inside `pair`, as written.
+fn identifier(pair: &Pair<Rule>) -> Result<String, ParseError> {
+    required(pair, Rule::identifier).map(|i| i.as_str().to_string())
+}
+
+/// Returns the identifiers of the first parenthesised list directly inside `pair`, such as `(a, b)`.
+fn identifier_list(pair: &Pair<Rule>) -> Result<Vec<String>, ParseError> {
+    let list = required(pair, Rule::identifier_list)?;
+    Ok(children(&list, Rule::identifier).map(|i| i.as_str().to_string()).collect())
+}
+
+/// Builds the value of an INSERT or UPDATE: an expression or the keyword DEFAULT.
+fn build_value(value: Pair<Rule>) -> Expr {
+    let inner = value.into_inner().next().expect("value has exactly one child");
+    match inner.as_rule() {
+        Rule::kw_default => Expr::Default,
+        _ => build_expr(inner),
+    }
+}
+
+/// Builds a key of an ORDER BY clause, such as `name DESC NULLS LAST`.
+fn build_sort_key(sort_key: Pair<Rule>) -> Result<SortKey, ParseError> {
+    let descending = child(&sort_key, Rule::kw_desc).is_some();
+    Ok(SortKey {
+        expr: build_expr(required(&sort_key, Rule::expr)?),
+        descending,
+        nulls_first: match (child(&sort_key, Rule::kw_first), child(&sort_key, Rule::kw_last)) {
+            (Some(_), _) => true,
+            (_, Some(_)) => false,
+            _ => descending,
+        },
+    })
+}
+
+/// Builds an expression from an `expr` pair or any of the rules it is made of.
+fn build_expr(expr: Pair<Rule>) -> Expr {
+    match expr.as_rule() {
+        Rule::expr | Rule::term => {
+            // Operators are left-associative: `a - b - c` is `(a - b) - c`
+            let mut parts = expr.into_inner();
+            let mut result = build_expr(parts.next().expect("expr has an operand"));
+            while let (Some(operator), Some(operand)) = (parts.next(), parts.next()) {
+                let operator = match operator.as_str() {
+                    "+" => ArithmeticOp::Add,
+                    "-" => ArithmeticOp::Subtract,
+                    "*" => ArithmeticOp::Multiply,
+                    "/" => ArithmeticOp::Divide,
+                    _ => ArithmeticOp::Modulo,
+                };
+                result = Expr::Binary {
+                    operator,
+                    left: Box::new(result),
+                    right: Box::new(build_expr(operand)),
+                };
+            }
+            result
         }
+        Rule::factor => {
+            let mut parts: Vec<Pair<Rule>> = expr.into_inner().collect();
+            let mut result = build_expr(parts.pop().expect("factor has an operand"));
+            for _ in parts {
+                result = Expr::Negate(Box::new(result));
+            }
+            result
+        }
+        Rule::literal => {
+            let literal = expr.into_inner().next().expect("literal has exactly one child");
+            match literal.as_rule() {
+                Rule::number => Expr::Literal(Literal::Number(literal.as_str().to_string())),
+                Rule::string => Expr::Literal(Literal::String(
+                    literal.into_inner().next().map_or(String::new(), |s| s.as_str().replace("''", "'")),
+                )),
+                _ => Expr::Literal(Literal::Null),
+            }
+        }
+        Rule::function_call => Expr::Function {
+            name: identifier(&expr).unwrap_or_default(),
+            args: children(&expr, Rule::expr).map(build_expr).collect(),
+        },
+        Rule::column_ref => {
+            let mut parts = expr.into_inner();
+            let column = parts.next().map_or(String::new(), |c| c.as_str().to_string());
+            let mut steps = Vec::new();
+            let mut as_text = false;
+            while let (Some(arrow), Some(step)) = (parts.next(), parts.next()) {
+                as_text = arrow.as_str() == "->>";
+                steps.push(match step.as_rule() {
+                    Rule::string => JsonPathStep::Key(step.into_inner().next().map_or(String::new(), |s| s.as_str().replace("''", "'"))),
+                    _ => JsonPathStep::Index(step.as_str().parse().unwrap_or(usize::MAX)),
+                });
+            }
+            if steps.is_empty() {
+                Expr::Identifier(column)
+            } else {
+                Expr::JsonPath { column, path: JsonPath { steps, as_text } }
+            }
+        }
+        _ => Expr::Identifier(expr.as_str().to_string()),
+    }
+}
 
-        Command::InspectTable {
-            name: rest.to_string(),
+/// Strips one pair of matching single or double quotes surrounding a value.
+pub fn unquote(value: &str) -> &str {
+    for quote in ['\'', '"'] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
         }
     }
-}
\ No newline at end of file
+    value
+}
+
+/// Returns the text of a double-quoted identifier such as `"MixedCase"`, with `""` unescaped,
+/// or `None` if `reference` is not quoted.
+fn quoted_identifier(reference: &str) -> Option<String> {
+    let inner = reference.strip_prefix('"')?.strip_suffix('"')?;
+    Some(inner.replace("\"\"", "\""))
+}
+
+/// Returns an identifier as it is stored: as written, without the quotes of a quoted identifier.
+pub fn identifier_name(reference: &str) -> String {
+    quoted_identifier(reference).unwrap_or_else(|| reference.to_string())
+}
+
+/// Returns a reference that refers to exactly the stored identifier `name`, e.g. `"MixedCase"`.
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Returns true if `reference`, an identifier as written in a statement, refers to the stored
+/// identifier `name`. Double-quoted identifiers are case-sensitive; others match regardless of case.
+pub fn identifier_matches(name: &str, reference: &str) -> bool {
+    match quoted_identifier(reference) {
+        Some(quoted) => name == quoted,
+        None => name.to_lowercase() == reference.to_lowercase(),
+    }
+}
@@ -0,0 +1,234 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Take, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+use uuid::Uuid;
+
+// External merge sort for ORDER BY. Records are collected in memory until they exceed the
+// memory limit; those are then sorted and spilled as a run to a temporary file of their own,
+// separate from the database file. The runs and the records still in memory are then merged
+// one record at a time, and the temporary file is removed once the merge is dropped.
+
+/// Bytes counted for each record and each of its fields on top of the text itself, roughly
+/// what a `Vec<String>` costs.
+const RECORD_OVERHEAD: usize = 24;
+
+/// Sorts `records` with `compare`, keeping records of about `memory_limit` bytes in memory at
+/// most while sorting. The sort is stable: records that compare equal keep their order. The
+/// sorted records are merged as they are read from the returned iterator.
+pub fn external_sort<I, F>(records: I, memory_limit: usize, compare: F) -> Result<SortedRecords<F>, String>
+where
+    I: IntoIterator<Item = Result<Vec<String>, String>>,
+    F: Fn(&[String], &[String]) -> Ordering,
+{
+    let mut spill = None;
+    let mut sources = Vec::new();
+    let mut buffer = Vec::new();
+    let mut buffered = 0;
+    for record in records {
+        let record = record?;
+        buffered += RECORD_OVERHEAD + record.iter().map(|f| f.len() + RECORD_OVERHEAD).sum::<usize>();
+        buffer.push(record);
+
+        if buffered > memory_limit {
+            buffer.sort_by(|a, b| compare(a, b));
+            let spill = match &mut spill {
+                Some(spill) => spill,
+                None => spill.insert(SpillFile::create()?),
+            };
+            let run = spill.write_run(&buffer)?;
+            sources.push(Source::Run(spill.open_run(run)?));
+            buffer.clear();
+            buffered = 0;
+        }
+    }
+    buffer.sort_by(|a, b| compare(a, b));
+
+    // The records still in memory came after all of the runs, so they are the last source
+    sources.push(Source::Memory(buffer.into_iter()));
+
+    let mut sorted = SortedRecords {
+        sources,
+        heads: BinaryHeap::new(),
+        compare: Rc::new(compare),
+        _spill: spill,
+    };
+    for source in 0..sorted.sources.len() {
+        sorted.advance(source)?;
+    }
+    Ok(sorted)
+}
+
+/// The sorted records, merged from the spilled runs and the records kept in memory.
+pub struct SortedRecords<F> {
+    sources: Vec<Source>,
+    /// The next record of each source that has one left.
+    heads: BinaryHeap<Head<F>>,
+    compare: Rc<F>,
+    // Dropped after the sources, which read from it
+    _spill: Option<SpillFile>,
+}
+
+impl<F: Fn(&[String], &[String]) -> Ordering> SortedRecords<F> {
+    /// Reads the next record of `source` into the heads, if it has one.
+    fn advance(&mut self, source: usize) -> Result<(), String> {
+        let record = match &mut self.sources[source] {
+            Source::Run(reader) => reader.next_record()?,
+            Source::Memory(records) => records.next(),
+        };
+        if let Some(record) = record {
+            self.heads.push(Head { record, source, compare: Rc::clone(&self.compare) });
+        }
+        Ok(())
+    }
+}
+
+impl<F: Fn(&[String], &[String]) -> Ordering> Iterator for SortedRecords<F> {
+    type Item = Result<Vec<String>, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Head { record, source, .. } = self.heads.pop()?;
+        if let Err(e) = self.advance(source) {
+            self.heads.clear();
+            return Some(Err(e));
+        }
+        Some(Ok(record))
+    }
+}
+
+/// Where the records being merged come from.
+enum Source {
+    Run(RunReader),
+    Memory(std::vec::IntoIter<Vec<String>>),
+}
+
+/// The next record of a source, ordered so that `BinaryHeap`, which pops its greatest item,
+/// pops the smallest record. Ties go to the earliest source, which holds the earliest records,
+/// keeping the sort stable.
+struct Head<F> {
+    record: Vec<String>,
+    source: usize,
+    compare: Rc<F>,
+}
+
+impl<F: Fn(&[String], &[String]) -> Ordering> Ord for Head<F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.compare)(&other.record, &self.record).then_with(|| other.source.cmp(&self.source))
+    }
+}
+
+impl<F: Fn(&[String], &[String]) -> Ordering> PartialOrd for Head<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: Fn(&[String], &[String]) -> Ordering> PartialEq for Head<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<F: Fn(&[String], &[String]) -> Ordering> Eq for Head<F> {}
+
+/// Where a run is stored in the spill file.
+#[derive(Clone, Copy)]
+struct Run {
+    offset: u64,
+    length: u64,
+}
+
+/// The temporary file the runs of one sort are spilled to, one after another. The file is
+/// removed when dropped, and is never shared, so concurrent sorts cannot disturb each other.
+struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    length: u64,
+}
+
+impl SpillFile {
+    fn create() -> Result<Self, String> {
+        let path = std::env::temp_dir().join(format!("isenta_sort_{}_{}.tmp", std::process::id(), Uuid::new_v4()));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to create sort file: {}", e))?;
+        Ok(SpillFile {
+            path,
+            writer: BufWriter::new(file),
+            length: 0,
+        })
+    }
+
+    /// Appends `records` as a run: per record a field count (u32), then each field as a
+    /// length (u32) and text.
+    fn write_run(&mut self, records: &[Vec<String>]) -> Result<Run, String> {
+        let offset = self.length;
+        for record in records {
+            self.write(&(record.len() as u32).to_le_bytes())?;
+            for field in record {
+                self.write(&(field.len() as u32).to_le_bytes())?;
+                self.write(field.as_bytes())?;
+            }
+        }
+        Ok(Run { offset, length: self.length - offset })
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.writer.write_all(bytes).map_err(|e| format!("Failed to write sort file: {}", e))?;
+        self.length += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Opens a reader over `run`, with a file handle of its own. Runs written later do not
+    /// disturb it.
+    fn open_run(&mut self, run: Run) -> Result<RunReader, String> {
+        let io_error = |e: std::io::Error| format!("Failed to read sort file: {}", e);
+        self.writer.flush().map_err(io_error)?;
+        let mut file = File::open(&self.path).map_err(io_error)?;
+        file.seek(SeekFrom::Start(run.offset)).map_err(io_error)?;
+        Ok(RunReader { input: BufReader::new(file.take(run.length)) })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Reads back the records of a run, buffering a little of it at a time.
+struct RunReader {
+    input: BufReader<Take<File>>,
+}
+
+impl RunReader {
+    /// Returns the next record of the run, or `None` at its end.
+    fn next_record(&mut self) -> Result<Option<Vec<String>>, String> {
+        if self.input.fill_buf().map_err(|e| format!("Failed to read sort file: {}", e))?.is_empty() {
+            return Ok(None);
+        }
+        let count = self.read_u32()?;
+        let mut record = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let length = self.read_u32()? as usize;
+            let bytes = self.read_bytes(length)?;
+            record.push(String::from_utf8(bytes).map_err(|_| "Invalid text in sort run".to_string())?);
+        }
+        Ok(Some(record))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<Vec<u8>, String> {
+        let mut bytes = vec![0; length];
+        self.input.read_exact(&mut bytes).map_err(|_| "Unexpected end of sort run".to_string())?;
+        Ok(bytes)
+    }
+}
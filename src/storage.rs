@@ -28,6 +28,7 @@ impl StorageEngine {
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(path)
             .expect("Could not open database file");
 
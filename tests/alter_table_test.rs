@@ -0,0 +1,87 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_add_column_backfills_existing_rows() {
+    let mut db = TestDb::new("alter_add");
+    db.run("CREATE TABLE users (id INTEGER, name TEXT)");
+    db.run("INSERT INTO users VALUES (1, alice)");
+    db.run("INSERT INTO users VALUES (2, bob)");
+
+    assert_eq!(db.run("ALTER TABLE users ADD COLUMN status VARCHAR(10) DEFAULT 'active'"), "Table 'users' altered successfully");
+    assert_eq!(db.run("ALTER TABLE users ADD seq SERIAL"), "Table 'users' altered successfully");
+    db.run("INSERT INTO users (id, name) VALUES (3, carol)");
+    db.reopen();
+
+    let result = db.run("SELECT * FROM users");
+    assert!(result.starts_with("id | name | status | seq"), "{}", result);
+    assert!(result.contains("1 | alice | active | 1"), "{}", result);
+    assert!(result.contains("3 | carol | active | 3"), "{}", result);
+
+    let result = db.run("ALTER TABLE users ADD COLUMN email TEXT NOT NULL");
+    assert_eq!(result, "Error: Column 'email' cannot be NULL");
+    let result = db.run("ALTER TABLE users ADD COLUMN name TEXT");
+    assert_eq!(result, "Error: Column 'name' already exists in table 'users'");
+    assert!(db.run("SELECT * FROM users").starts_with("id | name | status | seq\n"));
+}
+
+#[test]
+fn test_drop_column() {
+    let mut db = TestDb::new("alter_drop");
+    db.run("CREATE TABLE items (id INTEGER, note TEXT, qty INTEGER CHECK (qty >= 0))");
+    db.run("INSERT INTO items VALUES (1, hello, 5)");
+
+    assert_eq!(db.run("ALTER TABLE items DROP COLUMN note"), "Table 'items' altered successfully");
+    db.reopen();
+    let result = db.run("SELECT * FROM items");
+    assert_eq!(result, "id | qty\n--------\n1 | 5");
+
+    let result = db.run("ALTER TABLE items DROP qty");
+    assert_eq!(result, "Error: Cannot drop column 'qty': it is used by CHECK (qty >= 0)");
+    let result = db.run("ALTER TABLE items DROP COLUMN missing");
+    assert_eq!(result, "Error: Column 'missing' not found in table 'items'");
+}
+
+#[test]
+fn test_rename_column_updates_constraints() {
+    let mut db = TestDb::new("alter_rename");
+    db.run("CREATE TABLE users (id INTEGER PRIMARY KEY, age INTEGER CHECK (age >= 0))");
+    db.run("CREATE TABLE orders (user_id INTEGER REFERENCES users (id))");
+    db.run("INSERT INTO users VALUES (1, 30)");
+
+    assert_eq!(db.run("ALTER TABLE users RENAME COLUMN id TO user_no"), "Table 'users' altered successfully");
+    assert_eq!(db.run("ALTER TABLE users RENAME age TO years"), "Table 'users' altered successfully");
+    db.reopen();
+
+    assert!(db.run("INSPECT users").contains("CHECK (years >= 0)"));
+    assert!(db.run("INSPECT orders").contains("REFERENCES users (user_no)"));
+    assert!(db.run("SELECT * FROM users WHERE user_no = 1").contains("1 | 30"));
+    assert!(db.run("INSERT INTO users VALUES (2, -1)").starts_with("Error: Row violates CHECK"));
+    assert_eq!(db.run("INSERT INTO orders VALUES (1)"), "Inserted 1 row into 'orders'");
+
+    let result = db.run("ALTER TABLE users RENAME COLUMN years TO user_no");
+    assert_eq!(result, "Error: Column 'user_no' already exists in table 'users'");
+}
+
+#[test]
+fn test_rename_table() {
+    let mut db = TestDb::new("alter_rename_table");
+    db.run("CREATE TABLE people (id SERIAL PRIMARY KEY, name TEXT)");
+    db.run("CREATE TABLE pets (owner INTEGER REFERENCES people)");
+    db.run("CREATE TABLE other (id INTEGER)");
+    db.run("INSERT INTO people (name) VALUES (alice)");
+
+    assert_eq!(db.run("ALTER TABLE people RENAME TO Other"), "Error: Table 'other' already exists");
+    assert_eq!(db.run("ALTER TABLE people RENAME TO users"), "Table 'people' altered successfully");
+    db.reopen();
+
+    assert_eq!(db.run("SHOW TABLES"), "Tables:\n- users\n- pets\n- other");
+    assert_eq!(db.run("SELECT * FROM people"), "Error: Table 'people' does not exist");
+    assert!(db.run("INSPECT pets").contains("REFERENCES users (id)"));
+    assert!(db.run("INSPECT users").contains("nextval('users_id_seq')"));
+
+    db.run("INSERT INTO users (name) VALUES (bob)");
+    assert!(db.run("SELECT * FROM users WHERE name = 'bob'").contains("2 | bob"));
+    assert_eq!(db.run("INSERT INTO pets VALUES (2)"), "Inserted 1 row into 'pets'");
+}
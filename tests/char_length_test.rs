@@ -0,0 +1,34 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_varchar_and_char_length_enforcement() {
+    let mut db = TestDb::new("char_length");
+    db.run("CREATE TABLE codes (id INTEGER, name VARCHAR(5), country CHAR(2))");
+
+    assert_eq!(db.run("INSERT INTO codes VALUES (1, 'Alice', 'DE')"), "Inserted 1 row into 'codes'");
+
+    let result = db.run("INSERT INTO codes VALUES (2, 'Charlie', 'US')");
+    assert!(result.starts_with("Error: Value too long for column 'name' of type VARCHAR(5)"), "{}", result);
+
+    let result = db.run("INSERT INTO codes VALUES (3, 'Bob', 'USA')");
+    assert!(result.contains("of type CHAR(2)"), "{}", result);
+
+    let result = db.run("UPDATE codes SET name = 'Alexandra' WHERE id = 1");
+    assert!(result.starts_with("Error: Value too long"), "{}", result);
+    assert!(db.run("SELECT name FROM codes").contains("Alice"));
+}
+
+#[test]
+fn test_type_parameters_persist_across_reopen() {
+    let mut db = TestDb::new("char_length_persist");
+    db.run("CREATE TABLE codes (id INTEGER, name VARCHAR(3))");
+    db.reopen();
+
+    let inspect = db.run("INSPECT codes");
+    assert!(inspect.contains("VARCHAR(3)"), "{}", inspect);
+
+    let result = db.run("INSERT INTO codes VALUES (1, 'abcd')");
+    assert!(result.starts_with("Error: Value too long"), "{}", result);
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_check_constraints_on_insert_and_update() {
+    let mut db = TestDb::new("check_write");
+    db.run("CREATE TABLE tickets (id INTEGER, age INTEGER CHECK (age >= 0), status TEXT, CHECK (status IN ('open', 'closed')))");
+
+    assert_eq!(db.run("INSERT INTO tickets VALUES (1, 30, 'open')"), "Inserted 1 row into 'tickets'");
+
+    let result = db.run("INSERT INTO tickets VALUES (2, -1, 'open')");
+    assert_eq!(result, "Error: Row violates CHECK (age >= 0) on table 'tickets'");
+
+    let result = db.run("INSERT INTO tickets VALUES (3, 5, 'pending')");
+    assert_eq!(result, "Error: Row violates CHECK (status IN ('open', 'closed')) on table 'tickets'");
+
+    // A NULL value does not violate a check
+    assert_eq!(db.run("INSERT INTO tickets (id, status) VALUES (4, 'closed')"), "Inserted 1 row into 'tickets'");
+
+    let result = db.run("UPDATE tickets SET status = 'archived' WHERE id = 1");
+    assert!(result.starts_with("Error: Row violates CHECK"), "{}", result);
+    assert!(db.run("SELECT status FROM tickets WHERE id = 1").contains("open"));
+}
+
+#[test]
+fn test_check_constraints_persist() {
+    let mut db = TestDb::new("check_persist");
+    db.run("CREATE TABLE accounts (id INTEGER, balance INTEGER, CHECK (balance >= 0))");
+    db.reopen();
+
+    assert!(db.run("INSPECT accounts").contains("CHECK (balance >= 0)"));
+    let result = db.run("INSERT INTO accounts VALUES (1, -5)");
+    assert!(result.starts_with("Error: Row violates CHECK"), "{}", result);
+}
+
+#[test]
+fn test_check_on_unknown_column_is_rejected() {
+    let mut db = TestDb::new("check_unknown");
+    let result = db.run("CREATE TABLE t (a INTEGER, CHECK (b > 0))");
+    assert_eq!(result, "Error: Column 'b' not found in table 't'");
+}
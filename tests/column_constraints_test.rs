@@ -0,0 +1,79 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_not_null_and_default() {
+    let mut db = TestDb::new("constraints_default");
+    db.run("CREATE TABLE users (id INTEGER NOT NULL, name TEXT NOT NULL DEFAULT 'anon', city TEXT DEFAULT 'Berlin, DE')");
+
+    assert_eq!(db.run("INSERT INTO users (id) VALUES (1)"), "Inserted 1 row into 'users'");
+    assert_eq!(db.run("INSERT INTO users VALUES (2, 'Bob', DEFAULT)"), "Inserted 1 row into 'users'");
+
+    let result = db.run("SELECT * FROM users");
+    assert!(result.contains("1 | anon | Berlin, DE"), "{}", result);
+    assert!(result.contains("2 | Bob | Berlin, DE"), "{}", result);
+
+    let result = db.run("INSERT INTO users (name) VALUES ('Carol')");
+    assert_eq!(result, "Error: Column 'id' cannot be NULL");
+
+    let result = db.run("UPDATE users SET name = NULL WHERE id = 2");
+    assert_eq!(result, "Error: Column 'name' cannot be NULL");
+
+    db.run("UPDATE users SET name = DEFAULT WHERE id = 2");
+    assert!(db.run("SELECT name FROM users WHERE id = 2").contains("anon"));
+}
+
+#[test]
+fn test_unique_allows_multiple_nulls() {
+    let mut db = TestDb::new("constraints_unique");
+    db.run("CREATE TABLE users (id INTEGER, email TEXT UNIQUE)");
+    db.run("INSERT INTO users VALUES (1, 'a@example.com')");
+    db.run("INSERT INTO users (id) VALUES (2)");
+    assert_eq!(db.run("INSERT INTO users (id) VALUES (3)"), "Inserted 1 row into 'users'");
+
+    let result = db.run("INSERT INTO users VALUES (4, 'a@example.com')");
+    assert_eq!(result, "Error: Duplicate unique key (email)=(a@example.com) in table 'users'");
+
+    let result = db.run("UPDATE users SET email = 'b@example.com'");
+    assert!(result.starts_with("Error: Duplicate unique key"), "{}", result);
+}
+
+#[test]
+fn test_constraints_are_persisted_and_inspected() {
+    let mut db = TestDb::new("constraints_inspect");
+    db.run("CREATE TABLE tokens (id UUID PRIMARY KEY DEFAULT gen_random_uuid(), owner TEXT NOT NULL UNIQUE)");
+    db.reopen();
+
+    let inspect = db.run("INSPECT tokens");
+    assert!(inspect.contains("PRIMARY KEY DEFAULT gen_random_uuid()"), "{}", inspect);
+    assert!(inspect.contains("NOT NULL UNIQUE"), "{}", inspect);
+
+    db.run("INSERT INTO tokens (owner) VALUES ('alice')");
+    db.run("INSERT INTO tokens (owner) VALUES ('bob')");
+    let result = db.run("SELECT id FROM tokens");
+    assert_eq!(result.lines().count(), 4, "{}", result);
+    assert!(result.lines().skip(2).all(|id| id.len() == 36), "{}", result);
+}
+
+#[test]
+fn test_unique_keys_follow_writes() {
+    let mut db = TestDb::new("constraints_unique_writes");
+    db.run("CREATE TABLE codes (id INTEGER PRIMARY KEY, code TEXT UNIQUE)");
+    db.run("INSERT INTO codes VALUES (1, 'a'); INSERT INTO codes VALUES (2, 'b')");
+
+    // A rejected write leaves the keys as they were, and a key moved away can be taken again
+    assert!(db.run("INSERT INTO codes VALUES (1, 'c')").starts_with("Error: Duplicate primary key"));
+    assert_eq!(db.run("INSERT INTO codes VALUES (3, 'c')"), "Inserted 1 row into 'codes'");
+    db.run("UPDATE codes SET code = 'z' WHERE id = 1");
+    assert_eq!(db.run("INSERT INTO codes VALUES (4, 'a')"), "Inserted 1 row into 'codes'");
+    assert!(db.run("INSERT INTO codes VALUES (5, 'z')").starts_with("Error: Duplicate unique key"));
+
+    db.run("DELETE FROM codes WHERE id = 2");
+    assert_eq!(db.run("INSERT INTO codes VALUES (2, 'b')"), "Inserted 1 row into 'codes'");
+    db.reopen();
+    assert_eq!(
+        db.run("INSERT INTO codes VALUES (6, 'b')"),
+        "Error: Duplicate unique key (code)=(b) in table 'codes'"
+    );
+}
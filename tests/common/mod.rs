@@ -0,0 +1,63 @@
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use rust_dbms::{engine::QueryEngine, execute_line, parser::Parser};
+
+/// A query engine backed by its own database file in the system temp directory.
+/// The file is removed when the value is dropped.
+pub struct TestDb {
+    path: PathBuf,
+    engine: QueryEngine,
+    parser: Parser,
+}
+
+impl TestDb {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("isenta_{}_{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let engine = QueryEngine::with_database(path.to_str().unwrap());
+
+        TestDb {
+            path,
+            engine,
+            parser: Parser::new(),
+        }
+    }
+
+    /// Executes `input` and returns the formatted result, as the CLI would print it.
+    pub fn run(&mut self, input: &str) -> String {
+        execute_line(input, &mut self.engine, &self.parser)
+    }
+
+    /// Limits the memory ORDER BY uses before spilling to a temporary file.
+    pub fn set_sort_memory(&mut self, bytes: usize) {
+        self.engine.set_sort_memory(bytes);
+    }
+
+    /// Reopens the database file, discarding all in-memory state.
+    pub fn reopen(&mut self) {
+        self.engine = QueryEngine::with_database(self.path.to_str().unwrap());
+    }
+
+    /// Opens another connection to the database file, with in-memory state of its own.
+    pub fn connect(&self) -> QueryEngine {
+        QueryEngine::with_database(self.path.to_str().unwrap())
+    }
+
+    /// Executes `input` on `engine`, another connection from `connect`.
+    pub fn run_on(&self, engine: &mut QueryEngine, input: &str) -> String {
+        execute_line(input, engine, &self.parser)
+    }
+
+    /// Returns the size of the database file in bytes.
+    pub fn file_len(&self) -> u64 {
+        std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0)
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::TestDb;
+
+fn setup(name: &str) -> TestDb {
+    let mut db = TestDb::new(name);
+    db.run("CREATE TABLE items (id INTEGER, qty INTEGER, name TEXT)");
+    db.run("INSERT INTO items VALUES (1, 5, 'apple')");
+    db.run("INSERT INTO items VALUES (2, 0, 'xylophone')");
+    db.run("INSERT INTO items VALUES (3, 12, 'pear')");
+    db.run("INSERT INTO items VALUES (4, NULL, 'xenon')");
+    db
+}
+
+#[test]
+fn test_and_or_not_with_parentheses() {
+    let mut db = setup("compound_select");
+    let ids = |db: &mut TestDb, condition: &str| db.run(&format!("SELECT id FROM items WHERE {}", condition));
+
+    assert_eq!(ids(&mut db, "(id = 1 OR qty > 10) AND NOT name LIKE 'x%'"), "id\n--\n1\n3");
+    assert_eq!(ids(&mut db, "id = 1 OR qty > 10 AND name = 'apple'"), "id\n--\n1");
+    assert_eq!(ids(&mut db, "NOT (id = 1 OR id = 2) AND name LIKE 'x%'"), "id\n--\n4");
+    assert_eq!(ids(&mut db, "not not id = 2"), "id\n--\n2");
+    // A comparison with NULL is unknown, and so is its negation
+    assert_eq!(ids(&mut db, "NOT qty > 1"), "id\n--\n2");
+    assert_eq!(ids(&mut db, "qty > 1 OR name = 'xenon'"), "id\n--\n1\n3\n4");
+}
+
+#[test]
+fn test_compound_conditions_in_update_and_delete() {
+    let mut db = setup("compound_write");
+    assert_eq!(db.run("UPDATE items SET qty = 1 WHERE qty = 0 OR (id > 3 AND name = 'xenon')"), "Updated 2 rows in 'items'");
+    assert_eq!(db.run("DELETE FROM items WHERE qty = 1 AND NOT id = 4"), "Deleted 1 rows from 'items'");
+    assert_eq!(db.run("SELECT id FROM items"), "id\n--\n1\n3\n4");
+}
+
+#[test]
+fn test_compound_check_constraint() {
+    let mut db = TestDb::new("compound_check");
+    db.run("CREATE TABLE stock (qty INTEGER, cap INTEGER, CHECK (qty >= 0 AND (cap > 10 OR qty <= 10)))");
+    assert_eq!(db.run("INSERT INTO stock VALUES (5, 8)"), "Inserted 1 row into 'stock'");
+    assert_eq!(db.run("INSERT INTO stock VALUES (50, 20)"), "Inserted 1 row into 'stock'");
+    let result = db.run("INSERT INTO stock VALUES (11, 8)");
+    assert_eq!(result, "Error: Row violates CHECK (qty >= 0 AND (cap > 10 OR qty <= 10)) on table 'stock'");
+
+    db.run("ALTER TABLE stock RENAME COLUMN qty TO amount");
+    db.reopen();
+    let result = db.run("INSERT INTO stock VALUES (-1, 20)");
+    assert_eq!(result, "Error: Row violates CHECK (amount >= 0 AND (cap > 10 OR amount <= 10)) on table 'stock'");
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_create_table_as_select() {
+    let mut db = TestDb::new("ctas_select");
+    db.run("CREATE TABLE users (id INTEGER PRIMARY KEY, name VARCHAR(20), age INTEGER)");
+    db.run("INSERT INTO users VALUES (1, Alice, 30)");
+    db.run("INSERT INTO users VALUES (2, Bob, 17)");
+    db.run("INSERT INTO users VALUES (3, Carol, 45)");
+
+    let result = db.run("CREATE TABLE adults AS SELECT id, name FROM users WHERE age >= 18");
+    assert_eq!(result, "Table 'adults' created with 2 rows");
+    db.reopen();
+
+    let inspect = db.run("INSPECT adults");
+    assert!(inspect.contains("id                   | INTEGER"), "{}", inspect);
+    assert!(inspect.contains("name                 | VARCHAR(20)"), "{}", inspect);
+    assert!(!inspect.contains("PRIMARY KEY"), "{}", inspect);
+    let rows = db.run("SELECT * FROM adults");
+    assert_eq!(rows, "id | name\n---------\n1 | Alice\n3 | Carol");
+
+    // The copy is an independent table
+    db.run("INSERT INTO adults VALUES (1, Dup)");
+    assert!(!db.run("SELECT * FROM users").contains("Dup"));
+
+    assert_eq!(db.run("CREATE TABLE adults AS SELECT * FROM users"), "Error: Table 'adults' already exists");
+    assert_eq!(db.run("CREATE TABLE IF NOT EXISTS adults AS SELECT * FROM users"), "Notice: table 'adults' already exists, skipping");
+}
+
+#[test]
+fn test_create_table_as_select_json_paths() {
+    let mut db = TestDb::new("ctas_json");
+    db.run("CREATE TABLE events (id INTEGER, doc JSON)");
+    db.run(r#"INSERT INTO events VALUES (1, '{"kind": "login", "meta": {"ip": "10.0.0.1"}}')"#);
+
+    let result = db.run("CREATE TABLE kinds AS SELECT id, doc->>'kind', doc->'meta' FROM events");
+    assert_eq!(result, "Table 'kinds' created with 1 rows");
+    let inspect = db.run("INSPECT kinds");
+    assert!(inspect.contains("kind                 | TEXT"), "{}", inspect);
+    assert!(inspect.contains("meta                 | JSON"), "{}", inspect);
+    assert!(db.run("SELECT * FROM kinds WHERE kind = 'login'").contains("1 | login"));
+}
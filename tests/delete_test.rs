@@ -0,0 +1,46 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_delete_with_where_clause() {
+    let mut db = TestDb::new("delete_where");
+    db.run("CREATE TABLE users (id INTEGER, name TEXT)");
+    db.run("INSERT INTO users VALUES (1, alice)");
+    db.run("INSERT INTO users VALUES (2, bob)");
+    db.run("INSERT INTO users VALUES (3, carol)");
+
+    assert_eq!(db.run("DELETE FROM users WHERE id >= 2"), "Deleted 2 rows from 'users'");
+    assert_eq!(db.run("DELETE FROM users WHERE name = 'nobody'"), "Deleted 0 rows from 'users'");
+    let result = db.run("SELECT * FROM users");
+    assert!(result.contains("alice") && !result.contains("bob") && !result.contains("carol"), "{}", result);
+
+    db.reopen();
+    assert!(!db.run("SELECT * FROM users").contains("bob"));
+    assert_eq!(db.run("DELETE FROM users"), "Deleted 1 rows from 'users'");
+    assert_eq!(db.run("SELECT * FROM users"), "No rows found in 'users'");
+
+    let result = db.run("DELETE FROM users WHERE missing = 1");
+    assert_eq!(result, "Error: Column 'missing' not found in table 'users'");
+}
+
+#[test]
+fn test_delete_applies_foreign_key_actions() {
+    let mut db = TestDb::new("delete_fk");
+    db.run("CREATE TABLE users (id INTEGER PRIMARY KEY)");
+    db.run("CREATE TABLE orders (id INTEGER, user_id INTEGER REFERENCES users ON DELETE CASCADE)");
+    db.run("CREATE TABLE reviews (user_id INTEGER REFERENCES users)");
+    db.run("INSERT INTO users VALUES (1)");
+    db.run("INSERT INTO users VALUES (2)");
+    db.run("INSERT INTO orders VALUES (10, 1)");
+    db.run("INSERT INTO orders VALUES (20, 2)");
+    db.run("INSERT INTO reviews VALUES (2)");
+
+    assert_eq!(db.run("DELETE FROM users WHERE id = 1"), "Deleted 1 rows from 'users'");
+    let orders = db.run("SELECT * FROM orders");
+    assert!(!orders.contains("10") && orders.contains("20"), "{}", orders);
+
+    let result = db.run("DELETE FROM users WHERE id = 2");
+    assert!(result.starts_with("Error: Cannot delete from 'users'"), "{}", result);
+    assert!(db.run("SELECT * FROM orders").contains("20"));
+}
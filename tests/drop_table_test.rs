@@ -0,0 +1,65 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_drop_table_unlinks_schema_chain() {
+    let mut db = TestDb::new("drop_chain");
+    db.run("CREATE TABLE first (id INTEGER)");
+    db.run("CREATE TABLE second (id INTEGER)");
+    db.run("CREATE TABLE third (id INTEGER)");
+    db.run("INSERT INTO third VALUES (3)");
+
+    // Dropping the head of the chain moves the schema root; dropping a middle table relinks it
+    assert_eq!(db.run("DROP TABLE first"), "Table 'first' dropped successfully");
+    db.reopen();
+    assert_eq!(db.run("SHOW TABLES"), "Tables:\n- second\n- third");
+
+    db.run("CREATE TABLE fourth (id INTEGER)");
+    assert_eq!(db.run("DROP TABLE third"), "Table 'third' dropped successfully");
+    db.reopen();
+    assert_eq!(db.run("SHOW TABLES"), "Tables:\n- second\n- fourth");
+    assert_eq!(db.run("SELECT * FROM third"), "Error: Table 'third' does not exist");
+
+    db.run("DROP TABLE second");
+    db.run("DROP TABLE fourth");
+    db.reopen();
+    assert_eq!(db.run("SHOW TABLES"), "No tables in database");
+}
+
+#[test]
+fn test_drop_table_if_exists_and_errors() {
+    let mut db = TestDb::new("drop_errors");
+    assert_eq!(db.run("DROP TABLE missing"), "Error: Table 'missing' does not exist");
+    assert_eq!(db.run("DROP TABLE IF EXISTS missing"), "Notice: table 'missing' does not exist, skipping");
+
+    db.run("CREATE TABLE users (id SERIAL PRIMARY KEY)");
+    db.run("CREATE TABLE orders (user_id INTEGER REFERENCES users)");
+    let result = db.run("DROP TABLE users");
+    assert!(result.starts_with("Error: Cannot drop table 'users'"), "{}", result);
+
+    db.run("DROP TABLE orders");
+    db.run("DROP TABLE users");
+    // The sequence of the SERIAL column went with the table
+    assert_eq!(db.run("CREATE TABLE users (id SERIAL)"), "Table 'users' created successfully");
+}
+
+#[test]
+fn test_dropped_pages_are_reused() {
+    let mut db = TestDb::new("drop_reuse");
+    db.run("CREATE TABLE big (id INTEGER, payload TEXT)");
+    let payload = "x".repeat(1000);
+    for i in 0..20 {
+        db.run(&format!("INSERT INTO big VALUES ({}, {})", i, payload));
+    }
+    let size = db.file_len();
+
+    db.run("DROP TABLE big");
+    db.run("CREATE TABLE big (id INTEGER, payload TEXT)");
+    for i in 0..20 {
+        db.run(&format!("INSERT INTO big VALUES ({}, {})", i, payload));
+    }
+    assert_eq!(db.file_len(), size);
+    db.reopen();
+    assert!(db.run("SELECT * FROM big WHERE id = 19").contains("19 | x"));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::TestDb;
+
+fn setup(name: &str) -> TestDb {
+    let mut db = TestDb::new(name);
+    db.run("CREATE TABLE orders (id INTEGER, price INTEGER, qty INTEGER, created_at INTEGER, updated_at INTEGER)");
+    db.run("INSERT INTO orders VALUES (1, 20, 3, 100, 100)");
+    db.run("INSERT INTO orders VALUES (2, 45, 4, 100, 250)");
+    db.run("INSERT INTO orders VALUES (3, 7, 10, 300, 200)");
+    db.run("INSERT INTO orders VALUES (4, NULL, 5, 400, 500)");
+    db
+}
+
+#[test]
+fn test_column_comparisons_and_arithmetic() {
+    let mut db = setup("expression_columns");
+    assert_eq!(db.run("SELECT id FROM orders WHERE updated_at > created_at"), "id\n--\n2\n4");
+    assert_eq!(db.run("SELECT id FROM orders WHERE price * qty > 100"), "id\n--\n2");
+    // A NULL operand makes the whole expression NULL, so row 4 matches neither way
+    assert_eq!(db.run("SELECT id FROM orders WHERE NOT price * qty > 100"), "id\n--\n1\n3");
+    assert_eq!(db.run("SELECT id FROM orders WHERE updated_at - created_at BETWEEN 1 AND 150"), "id\n--\n2\n4");
+    assert_eq!(db.run("DELETE FROM orders WHERE qty % 2 = 0 AND price < qty"), "Deleted 1 rows from 'orders'");
+}
+
+#[test]
+fn test_precedence_and_unary_minus() {
+    let mut db = setup("expression_precedence");
+    assert_eq!(db.run("SELECT id FROM orders WHERE id = 1 + 2 * 1"), "id\n--\n3");
+    assert_eq!(db.run("SELECT id FROM orders WHERE id = (1 + 2) * 1 - 1"), "id\n--\n2");
+    assert_eq!(db.run("SELECT id FROM orders WHERE -price < -40"), "id\n--\n2");
+    // Integer division truncates; a REAL operand makes the result fractional
+    assert_eq!(db.run("SELECT id FROM orders WHERE price / 2 = 3"), "id\n--\n3");
+    assert_eq!(db.run("SELECT id FROM orders WHERE price / 2.0 = 3.5"), "id\n--\n3");
+}
+
+#[test]
+fn test_expression_errors() {
+    let mut db = setup("expression_errors");
+    assert_eq!(db.run("SELECT id FROM orders WHERE price / (qty - 3) > 1"), "Error: Division by zero");
+    assert_eq!(db.run("SELECT id FROM orders WHERE price * cost > 1"), "Error: Column 'cost' not found in table 'orders'");
+    // A misspelled column on the right is an error too, rather than text to compare with
+    assert_eq!(db.run("SELECT id FROM orders WHERE price > qyt"), "Error: Column 'qyt' not found in table 'orders'");
+    db.run("CREATE TABLE stock (item TEXT, qty INTEGER, cap INTEGER, CHECK (qty <= cap))");
+    assert_eq!(db.run("INSERT INTO stock VALUES ('bolt', 5, 10)"), "Inserted 1 row into 'stock'");
+    assert!(db.run("INSERT INTO stock VALUES ('nut', 11, 10)").contains("CHECK (qty <= cap)"));
+    assert!(db.run("ALTER TABLE stock DROP COLUMN cap").contains("it is used by CHECK (qty <= cap)"));
+}
+
+#[test]
+fn test_update_set_expressions() {
+    let mut db = setup("expression_update");
+    assert_eq!(db.run("UPDATE orders SET qty = qty + 1 WHERE id <= 2"), "Updated 2 rows in 'orders'");
+    assert_eq!(db.run("SELECT id, qty FROM orders WHERE id <= 2"), "id | qty\n--------\n1 | 4\n2 | 5");
+    // Every row is computed from its values before the update
+    db.run("UPDATE orders SET created_at = updated_at * 2 - created_at");
+    assert_eq!(db.run("SELECT created_at FROM orders"), "created_at\n----------\n100\n400\n100\n600");
+    assert_eq!(db.run("UPDATE orders SET qty = qty * cost WHERE id = 9"), "Error: Column 'cost' not found in table 'orders'");
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_foreign_key_rejects_orphans() {
+    let mut db = TestDb::new("fk_orphans");
+    db.run("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)");
+    db.run("CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users (id))");
+    db.run("INSERT INTO users VALUES (1, alice)");
+
+    assert_eq!(db.run("INSERT INTO orders VALUES (10, 1)"), "Inserted 1 row into 'orders'");
+    let result = db.run("INSERT INTO orders VALUES (11, 5)");
+    assert_eq!(result, "Error: Foreign key (user_id)=(5) has no matching row in 'users'");
+
+    // A NULL foreign key references nothing
+    assert_eq!(db.run("INSERT INTO orders (id) VALUES (12)"), "Inserted 1 row into 'orders'");
+
+    let result = db.run("UPDATE orders SET user_id = 7 WHERE id = 10");
+    assert!(result.starts_with("Error: Foreign key (user_id)=(7)"), "{}", result);
+
+    let result = db.run("UPDATE users SET id = 2 WHERE id = 1");
+    assert!(result.starts_with("Error: Cannot change 'users'"), "{}", result);
+
+    let result = db.run("CREATE TABLE bad (x INTEGER REFERENCES missing)");
+    assert_eq!(result, "Error: Referenced table 'missing' does not exist");
+}
+
+#[test]
+fn test_foreign_key_on_delete_actions() {
+    let mut db = TestDb::new("fk_actions");
+    db.run("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)");
+    db.run("CREATE TABLE orders (id INTEGER, user_id INTEGER, FOREIGN KEY (user_id) REFERENCES users ON DELETE CASCADE)");
+    db.run("CREATE TABLE notes (id INTEGER, user_id INTEGER REFERENCES users (id) ON DELETE SET NULL)");
+    db.run("INSERT INTO users VALUES (1, alice)");
+    db.run("INSERT INTO orders VALUES (10, 1)");
+    db.run("INSERT INTO notes VALUES (20, 1)");
+
+    db.run("TRUNCATE TABLE users");
+    assert!(!db.run("SELECT * FROM orders").contains("10"));
+    let notes = db.run("SELECT * FROM notes");
+    assert!(notes.contains("20") && !notes.contains("| 1"), "{}", notes);
+
+    db.run("CREATE TABLE teams (id INTEGER PRIMARY KEY)");
+    db.run("CREATE TABLE members (team_id INTEGER REFERENCES teams ON DELETE RESTRICT)");
+    db.run("INSERT INTO teams VALUES (1)");
+    db.run("INSERT INTO members VALUES (1)");
+    let result = db.run("TRUNCATE TABLE teams");
+    assert!(result.starts_with("Error: Cannot delete from 'teams'"), "{}", result);
+    assert!(db.run("SELECT * FROM teams").contains('1'));
+}
+
+#[test]
+fn test_foreign_keys_persist() {
+    let mut db = TestDb::new("fk_persist");
+    db.run("CREATE TABLE users (id INTEGER PRIMARY KEY)");
+    db.run("CREATE TABLE orders (user_id INTEGER REFERENCES users ON DELETE CASCADE)");
+    db.reopen();
+
+    let inspect = db.run("INSPECT orders");
+    assert!(inspect.contains("FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE"), "{}", inspect);
+    let result = db.run("INSERT INTO orders VALUES (3)");
+    assert!(result.starts_with("Error: Foreign key"), "{}", result);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_identifier_case_is_preserved() {
+    let mut db = TestDb::new("ident_case");
+    assert_eq!(db.run("CREATE TABLE Customers (Id INTEGER PRIMARY KEY, FullName TEXT)"), "Table 'Customers' created successfully");
+    db.run("INSERT INTO customers (ID, fullname) VALUES (1, alice)");
+    db.reopen();
+
+    assert_eq!(db.run("SHOW TABLES"), "Tables:\n- Customers");
+    let inspect = db.run("INSPECT CUSTOMERS");
+    assert!(inspect.contains("Id                   | INTEGER"), "{}", inspect);
+    assert!(inspect.contains("FullName             | TEXT"), "{}", inspect);
+    assert_eq!(db.run("SELECT * FROM customers WHERE FULLNAME = 'alice'"), "Id | FullName\n-------------\n1 | alice");
+    assert_eq!(db.run("CREATE TABLE CUSTOMERS (id INTEGER)"), "Error: Table 'CUSTOMERS' already exists");
+}
+
+#[test]
+fn test_quoted_identifiers_are_case_sensitive() {
+    let mut db = TestDb::new("ident_quoted");
+    db.run("CREATE TABLE \"MixedCase\" (\"Value\" INTEGER, \"value\" TEXT)");
+    db.run("INSERT INTO \"MixedCase\" VALUES (1, one)");
+
+    assert_eq!(db.run("SHOW TABLES"), "Tables:\n- MixedCase");
+    assert!(db.run("SELECT * FROM mixedcase").contains("1 | one"));
+    assert_eq!(db.run("SELECT * FROM \"mixedcase\""), "Error: Table '\"mixedcase\"' does not exist");
+    assert!(db.run("SELECT * FROM \"MixedCase\" WHERE \"Value\" = 1").contains("1 | one"));
+    assert!(db.run("SELECT * FROM \"MixedCase\" WHERE value = 'one'").contains("1 | one"));
+
+    let result = db.run("CREATE TABLE t (a INTEGER, A TEXT)");
+    assert_eq!(result, "Error: Column 'A' specified more than once");
+}
+
+#[test]
+fn test_tables_differing_only_in_case() {
+    let mut db = TestDb::new("ident_coexist");
+    db.run("CREATE TABLE \"Users\" (id INTEGER)");
+    assert_eq!(db.run("CREATE TABLE users (id INTEGER)"), "Error: Table 'users' already exists");
+    assert_eq!(db.run("CREATE TABLE \"users\" (id INTEGER)"), "Table 'users' created successfully");
+    db.run("INSERT INTO \"Users\" VALUES (1)");
+    db.run("INSERT INTO users VALUES (2)");
+    db.reopen();
+
+    assert_eq!(db.run("SHOW TABLES"), "Tables:\n- Users\n- users");
+    assert!(db.run("SELECT * FROM \"Users\"").ends_with("\n1"));
+    assert!(db.run("SELECT * FROM users").ends_with("\n2"));
+    // Unquoted names match regardless of case, so the spelling makes no difference
+    assert!(db.run("SELECT * FROM USERS").ends_with("\n2"));
+    assert!(db.run("SELECT * FROM Users").ends_with("\n2"));
+    assert_eq!(db.run("DROP TABLE \"Users\""), "Table 'Users' dropped successfully");
+    assert!(db.run("SELECT * FROM users").ends_with("\n2"));
+}
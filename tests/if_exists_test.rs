@@ -0,0 +1,35 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_create_and_drop_table_are_idempotent() {
+    let mut db = TestDb::new("if_exists_tables");
+    let create = "CREATE TABLE IF NOT EXISTS users (id INTEGER PRIMARY KEY, name TEXT)";
+    assert_eq!(db.run(create), "Table 'users' created successfully");
+    db.run("INSERT INTO users VALUES (1, alice)");
+    assert_eq!(db.run(create), "Notice: table 'users' already exists, skipping");
+    assert!(db.run("SELECT * FROM users").contains("alice"));
+
+    assert_eq!(db.run("CREATE TABLE users (id INTEGER)"), "Error: Table 'users' already exists");
+
+    assert_eq!(db.run("DROP TABLE IF EXISTS users"), "Table 'users' dropped successfully");
+    assert_eq!(db.run("DROP TABLE IF EXISTS users"), "Notice: table 'users' does not exist, skipping");
+}
+
+#[test]
+fn test_create_and_drop_sequence_are_idempotent() {
+    let mut db = TestDb::new("if_exists_sequences");
+    assert_eq!(db.run("CREATE SEQUENCE IF NOT EXISTS ids START WITH 5"), "Sequence 'ids' created successfully");
+    assert_eq!(db.run("CREATE SEQUENCE IF NOT EXISTS ids"), "Notice: sequence 'ids' already exists, skipping");
+
+    db.run("CREATE TABLE t (id INTEGER DEFAULT nextval('ids'))");
+    let result = db.run("DROP SEQUENCE ids");
+    assert_eq!(result, "Error: Cannot drop sequence 'ids': it is used by column 'id' of 't'");
+
+    db.run("DROP TABLE t");
+    assert_eq!(db.run("DROP SEQUENCE ids"), "Sequence 'ids' dropped successfully");
+    db.reopen();
+    assert_eq!(db.run("DROP SEQUENCE IF EXISTS ids"), "Notice: sequence 'ids' does not exist, skipping");
+    assert_eq!(db.run("DROP SEQUENCE ids"), "Error: Sequence 'ids' does not exist");
+}
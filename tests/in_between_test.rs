@@ -0,0 +1,39 @@
+mod common;
+
+use common::TestDb;
+
+fn setup(name: &str) -> TestDb {
+    let mut db = TestDb::new(name);
+    db.run("CREATE TABLE tickets (id INTEGER, status TEXT, ref UUID)");
+    db.run("INSERT INTO tickets VALUES (1, 'open', 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11')");
+    db.run("INSERT INTO tickets VALUES (2, 'closed', 'b0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11')");
+    db.run("INSERT INTO tickets VALUES (9, 'Pending', 'c0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11')");
+    db.run("INSERT INTO tickets VALUES (10, NULL, NULL)");
+    db
+}
+
+#[test]
+fn test_in_and_not_in() {
+    let mut db = setup("in_not_in");
+    assert_eq!(db.run("SELECT id FROM tickets WHERE status IN ('open', 'pending')"), "id\n--\n1\n9");
+    assert_eq!(db.run("SELECT id FROM tickets WHERE status NOT IN ('open', 'pending')"), "id\n--\n2");
+    // Integers compare by value, and a NULL in the list makes NOT IN unknown for other values
+    assert_eq!(db.run("SELECT id FROM tickets WHERE id IN (01, 10)"), "id\n--\n1\n10");
+    assert_eq!(db.run("SELECT id FROM tickets WHERE id NOT IN (1, NULL)"), "No rows found in 'tickets'");
+    assert_eq!(db.run("DELETE FROM tickets WHERE id NOT IN (1, 2)"), "Deleted 2 rows from 'tickets'");
+}
+
+#[test]
+fn test_between_and_not_between() {
+    let mut db = setup("between");
+    assert_eq!(db.run("SELECT id FROM tickets WHERE id BETWEEN 2 AND 9"), "id\n--\n2\n9");
+    assert_eq!(db.run("SELECT id FROM tickets WHERE id NOT BETWEEN 2 AND 9"), "id\n--\n1\n10");
+    // The AND of BETWEEN binds to its bounds before the AND of the condition
+    assert_eq!(db.run("SELECT id FROM tickets WHERE id BETWEEN 1 AND 9 AND status = 'closed'"), "id\n--\n2");
+    let result = db.run(
+        "SELECT id FROM tickets WHERE ref BETWEEN 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11' \
+         AND 'b1eebc99-9c0b-4ef8-bb6d-6bb9bd380a11'",
+    );
+    assert_eq!(result, "id\n--\n1\n2");
+    assert_eq!(db.run("UPDATE tickets SET status = 'old' WHERE id BETWEEN 1 AND 2"), "Updated 2 rows in 'tickets'");
+}
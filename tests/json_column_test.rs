@@ -0,0 +1,55 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_json_column_rejects_invalid_documents() {
+    let mut db = TestDb::new("json_invalid");
+    db.run("CREATE TABLE docs (id INTEGER, doc JSON)");
+
+    let result = db.run(r#"INSERT INTO docs VALUES (1, '{"name": "Alice", "age": 30}')"#);
+    assert_eq!(result, "Inserted 1 row into 'docs'");
+
+    let result = db.run("INSERT INTO docs VALUES (2, '{name: Bob}')");
+    assert!(result.starts_with("Error: Invalid JSON for column"), "{}", result);
+
+    let result = db.run("UPDATE docs SET doc = '[1, 2' WHERE id = 1");
+    assert!(result.starts_with("Error: Invalid JSON for column"), "{}", result);
+}
+
+#[test]
+fn test_json_path_operators() {
+    let mut db = TestDb::new("json_path");
+    db.run("CREATE TABLE docs (id INTEGER, doc JSON)");
+    db.run(r#"INSERT INTO docs VALUES (1, '{"name": "Alice", "age": 30, "tags": ["admin"]}')"#);
+    db.run(r#"INSERT INTO docs VALUES (2, '{"name": "Bob", "age": 25, "tags": []}')"#);
+
+    let result = db.run("SELECT id, doc->>'name' FROM docs WHERE doc->>'age' > 26");
+    assert!(result.contains("1 | Alice"), "{}", result);
+    assert!(!result.contains("Bob"), "{}", result);
+
+    let result = db.run("SELECT doc->'name', doc->'tags'->>0 FROM docs WHERE doc->>'name' = 'Alice'");
+    assert!(result.contains("\"Alice\" | admin"), "{}", result);
+
+    let result = db.run("SELECT id FROM docs WHERE doc->>'missing' = 'x'");
+    assert_eq!(result, "No rows found in 'docs'");
+
+    let result = db.run("SELECT id->>'name' FROM docs");
+    assert!(result.starts_with("Error: Operator -> requires a JSON column"), "{}", result);
+}
+
+#[test]
+fn test_json_fractional_numbers() {
+    let mut db = TestDb::new("json_fractions");
+    db.run("CREATE TABLE items (id INTEGER, doc JSON)");
+    db.run(r#"INSERT INTO items VALUES (1, '{"price": 10.5}')"#);
+    db.run(r#"INSERT INTO items VALUES (2, '{"price": 4.5}')"#);
+    db.run(r#"INSERT INTO items VALUES (3, '{"price": 7}')"#);
+
+    // Fractions compare and sort as numbers, not as text where '10.5' < '4.5'
+    let result = db.run("SELECT id FROM items WHERE doc->>'price' > 5 ORDER BY id");
+    assert_eq!(result, "id\n--\n1\n3");
+
+    let result = db.run("SELECT id FROM items ORDER BY doc->>'price'");
+    assert_eq!(result, "id\n--\n2\n3\n1");
+}
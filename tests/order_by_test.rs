@@ -0,0 +1,80 @@
+mod common;
+
+use common::TestDb;
+
+fn setup(name: &str) -> TestDb {
+    let mut db = TestDb::new(name);
+    db.run("CREATE TABLE people (name TEXT, team TEXT, score INTEGER)");
+    db.run(
+        "INSERT INTO people VALUES ('bob', 'red', 9); \
+         INSERT INTO people VALUES ('Alice', 'blue', 10); \
+         INSERT INTO people VALUES ('carol', 'red', NULL); \
+         INSERT INTO people VALUES ('dave', 'Blue', 9)",
+    );
+    db
+}
+
+fn names(result: &str) -> Vec<&str> {
+    result.lines().skip(2).collect()
+}
+
+#[test]
+fn test_multiple_keys_and_directions() {
+    let mut db = setup("order_by_keys");
+    // INTEGER sorts numerically and TEXT ignores case; equal keys keep their insertion order
+    assert_eq!(names(&db.run("SELECT name FROM people ORDER BY score")), ["bob", "dave", "Alice", "carol"]);
+    assert_eq!(names(&db.run("SELECT name FROM people ORDER BY name")), ["Alice", "bob", "carol", "dave"]);
+    assert_eq!(
+        names(&db.run("SELECT name FROM people ORDER BY team DESC, score ASC")),
+        ["bob", "carol", "dave", "Alice"]
+    );
+    assert_eq!(
+        names(&db.run("SELECT name FROM people WHERE score > 0 ORDER BY score * -1, name DESC")),
+        ["Alice", "dave", "bob"]
+    );
+
+    // INT is INTEGER, so it sorts numerically too
+    db.run("CREATE TABLE ids (id INT); INSERT INTO ids VALUES (10); INSERT INTO ids VALUES (2); INSERT INTO ids VALUES (9)");
+    assert_eq!(names(&db.run("SELECT id FROM ids ORDER BY id")), ["2", "9", "10"]);
+}
+
+#[test]
+fn test_nulls_aliases_and_positions() {
+    let mut db = setup("order_by_nulls");
+    // NULLs sort as larger than any value unless NULLS FIRST or LAST says otherwise
+    assert_eq!(names(&db.run("SELECT name FROM people ORDER BY score DESC")), ["carol", "Alice", "bob", "dave"]);
+    assert_eq!(names(&db.run("SELECT name FROM people ORDER BY score NULLS FIRST")), ["carol", "bob", "dave", "Alice"]);
+    assert_eq!(names(&db.run("SELECT name FROM people ORDER BY score DESC NULLS LAST")), ["Alice", "bob", "dave", "carol"]);
+    assert_eq!(
+        db.run("SELECT UPPER(name) AS n, score FROM people WHERE score = 9 ORDER BY n DESC"),
+        "n | score\n---------\nDAVE | 9\nBOB | 9"
+    );
+    assert_eq!(names(&db.run("SELECT team, name FROM people ORDER BY 2 DESC")), ["Blue | dave", "red | carol", "red | bob", "blue | Alice"]);
+    assert_eq!(db.run("SELECT name FROM people ORDER BY 3"), "Error: ORDER BY position 3 is not in the select list");
+    assert_eq!(db.run("SELECT name FROM people ORDER BY rank"), "Error: Column 'rank' not found in table 'people'");
+}
+
+#[test]
+fn test_sort_spills_to_a_temporary_file() {
+    let mut db = TestDb::new("order_by_spill");
+    db.run("CREATE TABLE numbers (n INTEGER, label TEXT)");
+    let script: Vec<String> = (0..300)
+        .map(|i| format!("INSERT INTO numbers VALUES ({}, 'label {}')", (i * 37) % 300, i % 7))
+        .collect();
+    db.run(&script.join(";"));
+
+    let in_memory = db.run("SELECT n, label FROM numbers ORDER BY label DESC, n");
+    let file_len = db.file_len();
+    db.set_sort_memory(512);
+    let spilled = db.run("SELECT n, label FROM numbers ORDER BY label DESC, n");
+    assert_eq!(spilled, in_memory);
+    assert_eq!(names(&spilled)[..2], ["2 | label 6", "15 | label 6"]);
+
+    // The runs go to a file of their own, which is removed once the sort is done
+    assert_eq!(db.file_len(), file_len);
+    let prefix = format!("isenta_sort_{}_", std::process::id());
+    let leftovers = std::fs::read_dir(std::env::temp_dir()).unwrap()
+        .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with(&prefix))
+        .count();
+    assert_eq!(leftovers, 0);
+}
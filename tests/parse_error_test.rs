@@ -0,0 +1,33 @@
+mod common;
+
+use common::TestDb;
+use rust_dbms::parser::Parser;
+
+#[test]
+fn test_parse_error_position_and_expected_tokens() {
+    let parser = Parser::new();
+    let error = parser.parse("SELECT id\nFROM users\nWHERE id == 1").unwrap_err();
+    assert_eq!(error.offset, 31);
+    assert_eq!((error.line, error.column), (3, 11));
+    assert_eq!(error.found, "=");
+    assert_eq!(error.expected, vec!["'('", "value"]);
+
+    let error = parser.parse("CREATE TABLE t").unwrap_err();
+    assert_eq!(error.found, "");
+    assert_eq!(error.expected, vec!["'('", "AS", "identifier"]);
+    assert_eq!(error.to_string(), "Syntax error at line 1, column 15: expected '(', AS or identifier, found end of input");
+}
+
+#[test]
+fn test_parse_error_is_rendered_with_caret() {
+    let mut db = TestDb::new("parse_error_caret");
+    assert_eq!(
+        db.run("SELECT name FORM users"),
+        "Error: Syntax error at line 1, column 13: expected '(', ',', '->', AS, FROM or operator, found 'FORM'\n\
+         SELECT name FORM users\n            ^"
+    );
+    assert_eq!(
+        db.run("GET users AS XML"),
+        "Error: Unsupported format: XML at line 1, column 14\nGET users AS XML\n             ^"
+    );
+}
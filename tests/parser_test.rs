@@ -0,0 +1,62 @@
+mod common;
+
+use common::TestDb;
+use rust_dbms::parser::{Command, Expr, JsonPath, JsonPathStep, Literal, Parser, Predicate, WhereClause};
+
+#[test]
+fn test_keywords_and_commas_inside_strings() {
+    let mut db = TestDb::new("parser_strings");
+    db.run("CREATE TABLE notes (id INTEGER, body TEXT)");
+    assert_eq!(db.run("INSERT INTO notes VALUES (1, 'from here, where to?')"), "Inserted 1 row into 'notes'");
+    db.run("INSERT INTO notes VALUES (2, 'SET x = 1')");
+
+    assert!(db.run("SELECT * FROM notes WHERE body = 'from here, where to?'").ends_with("\n1 | from here, where to?"));
+    db.run("UPDATE notes SET body = 'a, b WHERE c' WHERE id = 2");
+    assert!(db.run("SELECT body FROM notes WHERE id = 2").ends_with("\na, b WHERE c"));
+}
+
+#[test]
+fn test_statement_ast() {
+    let parser = Parser::new();
+    match parser.parse("insert into t (a, nullable) values (null, 'x', default)") {
+        Ok(Command::Insert { table, columns, values }) => {
+            assert_eq!(table, "t");
+            assert_eq!(columns, vec!["a".to_string(), "nullable".to_string()]);
+            assert_eq!(
+                values,
+                vec![Expr::Literal(Literal::Null), Expr::Literal(Literal::String("x".to_string())), Expr::Default]
+            );
+        }
+        other => panic!("unexpected command: {:?}", other),
+    }
+    assert!(parser.parse("SELECT * FROM").is_err());
+}
+
+#[test]
+fn test_condition_and_default_ast() {
+    let parser = Parser::new();
+    match parser.parse("SELECT * FROM t WHERE doc->'tags'->>0 NOT IN ('a', 'b')") {
+        Ok(Command::Select { where_clause: Some(WhereClause::Predicate(predicate)), .. }) => {
+            let path = JsonPath { steps: vec![JsonPathStep::Key("tags".to_string()), JsonPathStep::Index(0)], as_text: true };
+            assert_eq!(
+                predicate,
+                Predicate::In {
+                    expr: Expr::JsonPath { column: "doc".to_string(), path },
+                    list: vec![Expr::Literal(Literal::String("a".to_string())), Expr::Literal(Literal::String("b".to_string()))],
+                    negated: true,
+                }
+            );
+        }
+        other => panic!("unexpected command: {:?}", other),
+    }
+
+    // Defaults are kept parsed and written back in a form that parses to the same expression
+    let mut db = TestDb::new("parser_default_ast");
+    db.run("CREATE TABLE t (id INTEGER, n INTEGER DEFAULT (1 + 2) * -(4 - 1), s TEXT DEFAULT 'it''s')");
+    let inspect = db.run("INSPECT t");
+    assert!(inspect.contains("DEFAULT (1 + 2) * -(4 - 1)"), "{}", inspect);
+    assert!(inspect.contains("DEFAULT 'it''s'"), "{}", inspect);
+    db.reopen();
+    db.run("INSERT INTO t (id) VALUES (1)");
+    assert_eq!(db.run("SELECT n, s FROM t"), "n | s\n-----\n-9 | it's");
+}
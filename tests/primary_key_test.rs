@@ -0,0 +1,47 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_primary_key_rejects_duplicates() {
+    let mut db = TestDb::new("pk_single");
+    db.run("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)");
+    db.run("INSERT INTO users VALUES (1, 'Alice')");
+    db.run("INSERT INTO users VALUES (2, 'Bob')");
+
+    let result = db.run("INSERT INTO users VALUES (1, 'Carol')");
+    assert_eq!(result, "Error: Duplicate primary key (id)=(1) in table 'users'");
+
+    let result = db.run("INSERT INTO users VALUES (NULL, 'Dave')");
+    assert!(result.starts_with("Error: Primary key column 'id' cannot be NULL"), "{}", result);
+
+    let result = db.run("UPDATE users SET id = 1 WHERE id = 2");
+    assert!(result.starts_with("Error: Duplicate primary key"), "{}", result);
+    assert!(db.run("SELECT name FROM users WHERE id = 2").contains("Bob"));
+
+    assert_eq!(db.run("UPDATE users SET id = 3 WHERE id = 2"), "Updated 1 rows in 'users'");
+}
+
+#[test]
+fn test_composite_primary_key_persists() {
+    let mut db = TestDb::new("pk_composite");
+    db.run("CREATE TABLE members (team INTEGER, user_id INTEGER, role TEXT, PRIMARY KEY (team, user_id))");
+    db.reopen();
+
+    let inspect = db.run("INSPECT members");
+    assert_eq!(inspect.matches("PRIMARY KEY").count(), 2, "{}", inspect);
+
+    db.run("INSERT INTO members VALUES (1, 1, 'owner')");
+    assert_eq!(db.run("INSERT INTO members VALUES (1, 2, 'member')"), "Inserted 1 row into 'members'");
+    assert_eq!(db.run("INSERT INTO members VALUES (2, 1, 'member')"), "Inserted 1 row into 'members'");
+
+    let result = db.run("INSERT INTO members VALUES (1, 2, 'admin')");
+    assert_eq!(result, "Error: Duplicate primary key (team, user_id)=(1, 2) in table 'members'");
+}
+
+#[test]
+fn test_multiple_primary_keys_are_rejected() {
+    let mut db = TestDb::new("pk_multiple");
+    let result = db.run("CREATE TABLE t (a INTEGER PRIMARY KEY, b INTEGER PRIMARY KEY)");
+    assert!(result.starts_with("Error: Multiple primary keys are not allowed at line 1, column 40"), "{}", result);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::TestDb;
+use rust_dbms::{engine::QueryEngine, execute_script, parser::Parser};
+
+#[test]
+fn test_script_runs_statements_in_order() {
+    let mut db = TestDb::new("script_order");
+    let result = db.run(
+        "CREATE TABLE t (id INTEGER, note TEXT); -- the table\n\
+         INSERT INTO t VALUES (1, 'a; b');\n\
+         /* a block\n   comment; with a semicolon */\n\
+         INSERT INTO t VALUES (2, '-- not a comment');\n\
+         SELECT * FROM t WHERE id = 1;",
+    );
+    assert_eq!(
+        result,
+        "Table 't' created successfully\n\n\
+         Inserted 1 row into 't'\n\n\
+         Inserted 1 row into 't'\n\n\
+         id | note\n---------\n1 | a; b"
+    );
+    assert!(db.run("SELECT note FROM t WHERE id = 2").ends_with("\n-- not a comment"));
+}
+
+#[test]
+fn test_script_results_per_statement() {
+    let path = std::env::temp_dir().join(format!("isenta_script_results_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut engine = QueryEngine::with_database(path.to_str().unwrap());
+    let parser = Parser::new();
+
+    let results = execute_script(
+        "CREATE TABLE t (id INTEGER);\nINSERT INTO t VALUES (1);\nINSERT t VALUES (2);\n;\nSELECT * FROM t",
+        &mut engine,
+        &parser,
+    );
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[1], "Inserted 1 row into 't'");
+    // Errors point into the script, and do not stop the statements after them
+    assert_eq!(
+        results[2],
+        "Error: Syntax error at line 3, column 8: expected INTO, found 't'\nINSERT t VALUES (2);\n       ^"
+    );
+    assert_eq!(results[3], "id\n--\n1");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_is_complete() {
+    let parser = Parser::new();
+    assert!(parser.is_complete("SELECT * FROM t"));
+    assert!(!parser.is_complete("SELECT *\n"));
+    assert!(parser.is_complete("SELECT *\nFROM -- the table\n;\n"));
+    assert!(!parser.is_complete("INSERT INTO t VALUES ('a;\n"));
+    assert!(parser.is_complete("INSERT INTO t VALUES ('a;\nb'); -- done"));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::TestDb;
+
+fn setup(name: &str) -> TestDb {
+    let mut db = TestDb::new(name);
+    db.run("CREATE TABLE items (name TEXT, price INTEGER, qty INTEGER)");
+    db.run("INSERT INTO items VALUES ('bolt', 2, 50)");
+    db.run("INSERT INTO items VALUES ('Nut', 3, NULL)");
+    db
+}
+
+#[test]
+fn test_expressions_and_aliases() {
+    let mut db = setup("select_aliases");
+    assert_eq!(
+        db.run("SELECT price * qty AS total, UPPER(name) AS n FROM items"),
+        "total | n\n---------\n100 | BOLT\n | NUT"
+    );
+    // Without an alias the header is the expression as written
+    assert_eq!(db.run("SELECT name, LENGTH(name), price / 2.0 FROM items WHERE price > 2"), "name | LENGTH(name) | price / 2.0\n---------------------------------\nNut | 3 | 1.5");
+    assert_eq!(db.run("SELECT price AS \"Unit Price\" FROM items WHERE name = 'bolt'"), "Unit Price\n----------\n2");
+    assert_eq!(db.run("SELECT cost * 2 FROM items"), "Error: Column 'cost' not found in table 'items'");
+}
+
+#[test]
+fn test_wildcards_mixed_with_expressions() {
+    let mut db = setup("select_wildcards");
+    assert_eq!(
+        db.run("SELECT items.*, price + 1 AS next FROM items WHERE price > 0").lines().next(),
+        Some("name | price | qty | next")
+    );
+    assert_eq!(db.run("SELECT LOWER(name), * FROM items WHERE price = 3"), "LOWER(name) | name | price | qty\n--------------------------------\nnut | Nut | 3 |");
+    assert_eq!(db.run("SELECT other.* FROM items"), "Error: Table 'other' is not in the FROM clause");
+}
+
+#[test]
+fn test_create_table_as_with_expressions() {
+    let mut db = setup("select_ctas");
+    assert_eq!(db.run("CREATE TABLE totals AS SELECT name AS item, price * qty AS total FROM items"), "Table 'totals' created with 2 rows");
+    let inspect = db.run("INSPECT totals");
+    assert!(inspect.contains("item                 | TEXT"), "{}", inspect);
+    assert!(inspect.contains("total                | INTEGER"), "{}", inspect);
+    assert_eq!(db.run("SELECT * FROM totals WHERE total > 10"), "item | total\n------------\nbolt | 100");
+}
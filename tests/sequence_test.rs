@@ -0,0 +1,99 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_serial_and_autoincrement_columns() {
+    let mut db = TestDb::new("seq_serial");
+    db.run("CREATE TABLE users (id SERIAL PRIMARY KEY, name TEXT)");
+    db.run("CREATE TABLE events (id INTEGER PRIMARY KEY AUTOINCREMENT, kind TEXT)");
+
+    db.run("INSERT INTO users (name) VALUES (alice)");
+    db.run("INSERT INTO users (name) VALUES (bob)");
+    let result = db.run("SELECT * FROM users WHERE name = 'bob'");
+    assert!(result.contains("2 | bob"), "{}", result);
+
+    // An explicit value moves the sequence past it
+    db.run("INSERT INTO users VALUES (10, carol)");
+    db.run("INSERT INTO users VALUES (DEFAULT, dave)");
+    assert!(db.run("SELECT * FROM users WHERE name = 'dave'").contains("11 | dave"));
+
+    db.run("INSERT INTO events (kind) VALUES (login)");
+    assert!(db.run("SELECT * FROM events").contains("1 | login"));
+
+    let inspect = db.run("INSPECT users");
+    assert!(inspect.contains("PRIMARY KEY NOT NULL AUTOINCREMENT DEFAULT nextval('users_id_seq')"), "{}", inspect);
+}
+
+#[test]
+fn test_create_sequence_and_nextval() {
+    let mut db = TestDb::new("seq_create");
+    assert_eq!(db.run("CREATE SEQUENCE ticket_no START WITH 100 INCREMENT BY 5"), "Sequence 'ticket_no' created successfully");
+    assert_eq!(db.run("CREATE SEQUENCE ticket_no"), "Error: Sequence 'ticket_no' already exists");
+
+    db.run("CREATE TABLE tickets (no INTEGER DEFAULT nextval('ticket_no'), title TEXT)");
+    db.run("INSERT INTO tickets (title) VALUES (first)");
+    db.run("INSERT INTO tickets VALUES (nextval('ticket_no'), second)");
+    let result = db.run("SELECT * FROM tickets");
+    assert!(result.contains("100 | first") && result.contains("105 | second"), "{}", result);
+
+    let result = db.run("INSERT INTO tickets VALUES (nextval('missing'), third)");
+    assert_eq!(result, "Error: Sequence 'missing' does not exist");
+}
+
+#[test]
+fn test_sequences_survive_reopen() {
+    let mut db = TestDb::new("seq_persist");
+    db.run("CREATE SEQUENCE counter");
+    db.run("CREATE TABLE items (id SERIAL, name TEXT)");
+    db.run("INSERT INTO items (name) VALUES (a)");
+    db.run("INSERT INTO items (name) VALUES (b)");
+    db.run("CREATE TABLE log (n INTEGER)");
+    db.run("INSERT INTO log VALUES (nextval('counter'))");
+    db.reopen();
+
+    db.run("INSERT INTO items (name) VALUES (c)");
+    assert!(db.run("SELECT * FROM items WHERE name = 'c'").contains("3 | c"));
+    db.run("INSERT INTO log VALUES (nextval('counter'))");
+    assert!(db.run("SELECT * FROM log").contains('2'));
+}
+
+#[test]
+fn test_many_sequences() {
+    let mut db = TestDb::new("seq_many");
+    // Far more than fit in a single page, each with a 30-character name
+    for i in 0..200 {
+        let result = db.run(&format!("CREATE SEQUENCE SEQUENCE_WITH_A_LONG_NAME_{:04}", i));
+        assert_eq!(result, format!("Sequence 'SEQUENCE_WITH_A_LONG_NAME_{:04}' created successfully", i));
+    }
+    assert_eq!(db.run("CREATE TABLE ORDERS (id SERIAL, item TEXT)"), "Table 'ORDERS' created successfully");
+    db.run("INSERT INTO orders (item) VALUES (pen)");
+    db.reopen();
+
+    db.run("INSERT INTO orders (item) VALUES (ink)");
+    assert!(db.run("SELECT * FROM orders WHERE item = 'ink'").contains("2 | ink"));
+    db.run("CREATE TABLE log (N INTEGER)");
+    db.run("INSERT INTO log VALUES (nextval('SEQUENCE_WITH_A_LONG_NAME_0199'))");
+    assert_eq!(db.run("SELECT * FROM log"), "N\n-\n1");
+
+    // Saving a new value writes over the existing pages
+    let file_len = db.file_len();
+    db.run("INSERT INTO log VALUES (nextval('SEQUENCE_WITH_A_LONG_NAME_0000'))");
+    assert_eq!(db.file_len(), file_len);
+}
+
+#[test]
+fn test_connections_share_sequences() {
+    let mut db = TestDb::new("seq_connections");
+    db.run("CREATE SEQUENCE counter");
+    db.run("CREATE TABLE first_log (n INTEGER); CREATE TABLE second_log (n INTEGER)");
+    let mut other = db.connect();
+
+    // Each connection sees the values the other has handed out
+    for _ in 0..2 {
+        db.run("INSERT INTO first_log VALUES (nextval('counter'))");
+        db.run_on(&mut other, "INSERT INTO second_log VALUES (nextval('counter'))");
+    }
+    assert_eq!(db.run("SELECT n FROM first_log"), "n\n-\n1\n3");
+    assert_eq!(db.run_on(&mut other, "SELECT n FROM second_log"), "n\n-\n2\n4");
+}
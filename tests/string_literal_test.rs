@@ -0,0 +1,37 @@
+mod common;
+
+use common::TestDb;
+
+#[test]
+fn test_string_literals_with_commas_parentheses_and_escapes() {
+    let mut db = TestDb::new("string_literal_insert");
+    db.run("CREATE TABLE people (id INTEGER, name TEXT, note TEXT)");
+    assert_eq!(
+        db.run("INSERT INTO people VALUES (1, 'Smith, John', 'O''Brien (née Kelly)')"),
+        "Inserted 1 row into 'people'"
+    );
+    db.run("INSERT INTO people VALUES (2, '', '''quoted''')");
+
+    assert_eq!(
+        db.run("SELECT * FROM people"),
+        "id | name | note\n----------------\n1 | Smith, John | O'Brien (née Kelly)\n2 |  | 'quoted'"
+    );
+    assert!(db.run("INSERT INTO people VALUES (3, 'unterminated)").starts_with("Error: Syntax error"));
+
+    db.run("ALTER TABLE people ADD COLUMN title TEXT DEFAULT 'Ma''am'");
+    db.run("INSERT INTO people (id) VALUES (3)");
+    assert!(db.run("SELECT title FROM people WHERE id = 3").ends_with("\nMa'am"));
+}
+
+#[test]
+fn test_quotes_are_handled_alike_in_update_and_where() {
+    let mut db = TestDb::new("string_literal_update");
+    db.run("CREATE TABLE people (id INTEGER, name TEXT)");
+    db.run("INSERT INTO people VALUES (1, 'Smith, John')");
+
+    assert!(db.run("SELECT id FROM people WHERE name = 'Smith, John'").ends_with("\n1"));
+    db.run("UPDATE people SET name = 'It''s, (me)' WHERE name = 'Smith, John'");
+    assert!(db.run("SELECT name FROM people WHERE id = 1").ends_with("\nIt's, (me)"));
+    assert!(db.run("SELECT id FROM people WHERE name = 'It''s, (me)'").ends_with("\n1"));
+    assert!(db.run("SELECT id FROM people WHERE name IN ('x', 'It''s, (me)')").ends_with("\n1"));
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::TestDb;
+
+fn setup(name: &str) -> TestDb {
+    let mut db = TestDb::new(name);
+    db.run("CREATE TABLE people (id INTEGER, name TEXT)");
+    for (id, name) in [(1, "alice"), (2, "Mallory"), (3, "bob"), (4, "Zoë"), (5, "mal")] {
+        db.run(&format!("INSERT INTO people VALUES ({}, '{}')", id, name));
+    }
+    db.run("INSERT INTO people VALUES (6, NULL)");
+    db
+}
+
+#[test]
+fn test_text_ordering_operators() {
+    let mut db = setup("text_ordering");
+    assert_eq!(db.run("SELECT id FROM people WHERE name > 'M'"), "id\n--\n2\n4\n5");
+    assert_eq!(db.run("SELECT id FROM people WHERE name < 'm'"), "id\n--\n1\n3");
+    assert_eq!(db.run("SELECT id FROM people WHERE name >= 'mal'"), "id\n--\n2\n4\n5");
+    assert_eq!(db.run("SELECT id FROM people WHERE name <= 'MAL'"), "id\n--\n1\n3\n5");
+}
+
+#[test]
+fn test_text_ordering_ignores_case_like_equality() {
+    let mut db = setup("text_collation");
+    assert_eq!(db.run("SELECT id FROM people WHERE name = 'ZOË'"), "id\n--\n4");
+    assert_eq!(db.run("SELECT id FROM people WHERE name BETWEEN 'B' AND 'mallory'"), "id\n--\n2\n3\n5");
+    db.run("CREATE TABLE codes (code TEXT CHECK (code >= 'a' AND code < 'n'))");
+    assert_eq!(db.run("INSERT INTO codes VALUES ('Kilo')"), "Inserted 1 row into 'codes'");
+    assert!(db.run("INSERT INTO codes VALUES ('November')").starts_with("Error: Row violates CHECK"));
+}
+
+#[test]
+fn test_int_columns_compare_as_numbers() {
+    let mut db = TestDb::new("int_alias");
+    db.run("CREATE TABLE items (id INT, n INT AUTOINCREMENT)");
+    for id in [10, 2, 9] {
+        db.run(&format!("INSERT INTO items (id) VALUES ({})", id));
+    }
+    // INT is INTEGER, so 10 is greater than 3 rather than less as it would be as text
+    assert_eq!(db.run("SELECT id, n FROM items WHERE id > 3"), "id | n\n------\n10 | 1\n9 | 3");
+    assert!(db.run("INSPECT items").contains("| INTEGER"));
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::TestDb;
+
+const ID: &str = "6f1c2b0e-8a4d-4c3e-9b1a-2d7e5f3a9c10";
+
+#[test]
+fn test_uuid_parsing_and_canonical_rendering() {
+    let mut db = TestDb::new("uuid_parse");
+    db.run("CREATE TABLE users (id UUID, name TEXT)");
+
+    // Upper-case and simple (unhyphenated) forms are accepted and rendered canonically.
+    db.run("INSERT INTO users VALUES ('6F1C2B0E-8A4D-4C3E-9B1A-2D7E5F3A9C10', 'Alice')");
+    db.run("INSERT INTO users VALUES ('00000000000040008000000000000001', 'Bob')");
+
+    let result = db.run("INSERT INTO users VALUES ('not-a-uuid', 'Carol')");
+    assert!(result.starts_with("Error: Invalid UUID for column"), "{}", result);
+
+    let result = db.run(&format!("SELECT id, name FROM users WHERE id = '{}'", ID));
+    assert!(result.contains(&format!("{} | Alice", ID)), "{}", result);
+
+    let result = db.run("SELECT name FROM users WHERE id < '10000000-0000-4000-8000-000000000000'");
+    assert!(result.contains("Bob"), "{}", result);
+    assert!(!result.contains("Alice"), "{}", result);
+
+    // Values survive the round trip through the 16-byte on-disk encoding.
+    db.reopen();
+    let result = db.run("SELECT id FROM users WHERE name = 'Bob'");
+    assert!(result.contains("00000000-0000-4000-8000-000000000001"), "{}", result);
+}
+
+#[test]
+fn test_gen_random_uuid() {
+    let mut db = TestDb::new("uuid_gen");
+    db.run("CREATE TABLE users (id UUID, name TEXT)");
+    db.run("INSERT INTO users VALUES (gen_random_uuid(), 'Alice')");
+    db.run("INSERT INTO users VALUES (gen_random_uuid(), 'Bob')");
+
+    db.run("UPDATE users SET id = gen_random_uuid()");
+    let result = db.run("SELECT id FROM users");
+    let ids: Vec<&str> = result.lines().skip(2).collect();
+    assert_eq!(ids.len(), 2, "{}", result);
+    assert_ne!(ids[0], ids[1]);
+    for id in ids {
+        assert_eq!(id.len(), 36, "{}", id);
+        assert_eq!(&id[14..15], "4", "{}", id);
+    }
+}